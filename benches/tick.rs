@@ -0,0 +1,72 @@
+//Native benchmarks of the tick kernels, run with `cargo bench`. criterion is a native only
+//dev-dependency, so wasm builds get an empty crate
+
+#![cfg_attr(target_arch = "wasm32", no_main)]
+#![cfg(not(target_arch = "wasm32"))]
+
+#[macro_use]
+extern crate criterion;
+extern crate wasm_game_of_life;
+
+use criterion::{BenchmarkId, Criterion};
+use wasm_game_of_life::mode::Mode;
+use wasm_game_of_life::universe::{Boundary, Universe};
+
+const SIZES: [u32; 3] = [64, 256, 1024];
+
+fn soup(size: u32) -> Universe {
+    let mut univ = Universe::empty(size, size);
+    univ.randomize(0.5, u64::from(size));
+    univ
+}
+
+//Two state rules: totalistic, Hensel notation and a non-Moore neighbourhood
+fn rules(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rules");
+    for rule in ["B3/S23", "B36/S23", "B2-a/S12", "B2/S34H"].iter() {
+        for &size in SIZES.iter() {
+            let mut univ = soup(size);
+            univ.set_rule(rule.parse().unwrap());
+            group.bench_with_input(BenchmarkId::new(*rule, size), &size, |b, _| b.iter(|| univ.tick()));
+        }
+    }
+    group.finish();
+}
+
+//The per cell path on twisted edges and the multi-state ones
+fn modes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("modes");
+    for &size in SIZES.iter() {
+        let mut klein = soup(size);
+        klein.set_boundary(Boundary::KleinBottle);
+        group.bench_with_input(BenchmarkId::new("klein-bottle", size), &size, |b, _| b.iter(|| klein.tick()));
+
+        let mut generations = soup(size);
+        generations.set_rule("B2/S345/4".parse().unwrap());
+        group.bench_with_input(BenchmarkId::new("generations", size), &size, |b, _| b.iter(|| generations.tick()));
+
+        let mut cyclic = soup(size);
+        cyclic.set_cyclic(14, 1);
+        cyclic.randomize(0.5, 1);
+        group.bench_with_input(BenchmarkId::new("cyclic", size), &size, |b, _| b.iter(|| cyclic.tick()));
+
+        let mut quad = soup(size);
+        quad.set_mode(Mode::QuadLife);
+        group.bench_with_input(BenchmarkId::new("quadlife", size), &size, |b, _| b.iter(|| quad.tick()));
+    }
+    group.finish();
+}
+
+//A still life with a single glider, where only the active cells get ticked
+fn sparse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sparse");
+    for &size in SIZES.iter() {
+        let mut univ = Universe::empty(size, size);
+        univ.set_cells(&[(0, 1), (1, 2), (2, 0), (2, 1), (2, 2), (20, 20), (20, 21), (21, 20), (21, 21)]).unwrap();
+        group.bench_with_input(BenchmarkId::new("glider", size), &size, |b, _| b.iter(|| univ.tick()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, rules, modes, sparse);
+criterion_main!(benches);
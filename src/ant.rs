@@ -0,0 +1,126 @@
+use wasm_bindgen::prelude::*;
+use error::Error;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    fn turn(self, turn: Turn) -> Direction {
+        let quarter_turns = match turn {
+            Turn::None => 0,
+            Turn::Right => 1,
+            Turn::UTurn => 2,
+            Turn::Left => 3,
+        };
+        match (self as u8 + quarter_turns) % 4 {
+            0 => Direction::Up,
+            1 => Direction::Right,
+            2 => Direction::Down,
+            _ => Direction::Left,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Turn {
+    Left,
+    Right,
+    None,
+    UTurn,
+}
+
+//Turmite walking on the grid: on a cell of color n it turns by the n-th letter of its rule,
+//repaints the cell with the next color and moves forward one cell
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ant {
+    id: u32,
+    row: u32,
+    column: u32,
+    direction: Direction,
+    turns: Vec<Turn>,
+}
+
+impl Ant {
+    //rule is made of L (left), R (right), N (no turn) and U (u-turn), "RL" is Langton's ant
+    pub fn new(id: u32, row: u32, column: u32, direction: Direction, rule: &str) -> Result<Ant, Error> {
+        let turns = rule.chars()
+            .map(|ch| match ch.to_ascii_uppercase() {
+                'L' => Ok(Turn::Left),
+                'R' => Ok(Turn::Right),
+                'N' => Ok(Turn::None),
+                'U' => Ok(Turn::UTurn),
+                _ => Err(Error::Invalid(format!("invalid turn '{}' in ant rule '{}'", ch, rule))),
+            })
+            .collect::<Result<Vec<Turn>, Error>>()?;
+        if turns.len() < 2 || turns.len() > 255 {
+            return Err(Error::Invalid(format!("ant rule '{}' needs between 2 and 255 turns", rule)));
+        }
+        Ok(Ant { id, row, column, direction, turns })
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn row(&self) -> u32 {
+        self.row
+    }
+
+    pub fn column(&self) -> u32 {
+        self.column
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    //Number of colors the ant cycles cells through
+    pub fn colors(&self) -> u8 {
+        self.turns.len() as u8
+    }
+
+    //Turn on a cell of the given color, move one cell forward on a height x width torus and
+    //return the color the cell is repainted with
+    pub fn step(&mut self, color: u8, height: u32, width: u32) -> u8 {
+        let color = color % self.colors();
+        self.direction = self.direction.turn(self.turns[color as usize]);
+        let (row, column) = (self.row % height, self.column % width);
+        let (row, column) = match self.direction {
+            Direction::Up => ((row + height - 1) % height, column),
+            Direction::Right => (row, (column + 1) % width),
+            Direction::Down => ((row + 1) % height, column),
+            Direction::Left => (row, (column + width - 1) % width),
+        };
+        self.row = row;
+        self.column = column;
+        (color + 1) % self.colors()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_turn_rules() {
+        assert!(Ant::new(0, 0, 0, Direction::Up, "RL").is_ok());
+        assert_eq!(Ant::new(0, 0, 0, Direction::Up, "rlr").map(|ant| ant.colors()), Ok(3));
+        assert!(Ant::new(0, 0, 0, Direction::Up, "R").is_err());
+        assert!(Ant::new(0, 0, 0, Direction::Up, "RX").is_err());
+    }
+
+    #[test]
+    fn should_turn_repaint_and_move() {
+        let mut ant = Ant::new(0, 0, 0, Direction::Up, "RL").unwrap();
+        assert_eq!(ant.step(0, 4, 4), 1);
+        assert_eq!((ant.row(), ant.column(), ant.direction()), (0, 1, Direction::Right));
+        assert_eq!(ant.step(1, 4, 4), 0);
+        assert_eq!((ant.row(), ant.column(), ant.direction()), (3, 1, Direction::Up));
+    }
+}
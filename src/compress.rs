@@ -0,0 +1,83 @@
+//gzip for pattern files and snapshots. Reading checks for the gzip magic bytes, so callers take
+//plain and compressed input alike; the compress feature brings the pure Rust deflate (miniz_oxide
+//through flate2), without it gzip input is turned down
+
+use cfg_if::cfg_if;
+use std::borrow::Cow;
+use error::Error;
+
+//First two bytes of every gzip stream
+pub const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+//Most bytes gzip input may inflate to (128MB). Share strings and downloads are untrusted, and a
+//few KB of gzip bomb would otherwise take the whole wasm memory
+pub const MAX_INFLATED: u64 = 1 << 27;
+
+pub fn is_gzip(bytes: &[u8]) -> bool {
+    bytes.starts_with(&GZIP_MAGIC)
+}
+
+//The bytes themselves, or what they inflate to when they are gzip
+pub fn maybe_gunzip(bytes: &[u8]) -> Result<Cow<'_, [u8]>, Error> {
+    if is_gzip(bytes) { gunzip(bytes).map(Cow::Owned) } else { Ok(Cow::Borrowed(bytes)) }
+}
+
+cfg_if! {
+    if #[cfg(feature = "compress")] {
+        use std::io::{Read, Write};
+        use wasm_bindgen::prelude::*;
+        use flate2::Compression;
+        use flate2::read::GzDecoder;
+        use flate2::write::GzEncoder;
+
+        pub fn gzip(bytes: &[u8]) -> Vec<u8> {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+            encoder.write_all(bytes).and_then(|_| encoder.finish()).expect("writing to a Vec can't fail")
+        }
+
+        //gzip of any bytes for JS, e.g. a TextEncoder'd RLE to save as .rle.gz
+        #[wasm_bindgen(js_name = gzip)]
+        pub fn gzip_js(bytes: &[u8]) -> Vec<u8> {
+            gzip(bytes)
+        }
+
+        pub fn gunzip(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+            inflate(bytes, MAX_INFLATED)
+        }
+
+        //Reads one byte past limit to tell a stream that ends right there from a longer one
+        fn inflate(bytes: &[u8], limit: u64) -> Result<Vec<u8>, Error> {
+            let mut inflated = Vec::new();
+            GzDecoder::new(bytes).take(limit + 1).read_to_end(&mut inflated)
+                .map_err(|e| Error::Invalid(format!("invalid gzip data: {}", e)))?;
+            if inflated.len() as u64 > limit {
+                return Err(Error::Invalid(format!("gzip data inflates to more than {} bytes", limit)));
+            }
+            Ok(inflated)
+        }
+    } else {
+        pub fn gunzip(_bytes: &[u8]) -> Result<Vec<u8>, Error> {
+            Err(Error::Invalid("reading gzip data needs the compress feature".to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_pass_plain_bytes_through() {
+        assert_eq!(maybe_gunzip(b"x = 1, y = 1\no!"), Ok(Cow::Borrowed(&b"x = 1, y = 1\no!"[..])));
+        #[cfg(feature = "compress")]
+        {
+            let zeros = vec![0; 10000];
+            let packed = gzip(&zeros);
+            assert!(is_gzip(&packed) && packed.len() < 100);
+            assert_eq!(maybe_gunzip(&packed).unwrap().into_owned(), zeros);
+            assert!(gunzip(&packed[..10]).is_err());
+            assert_eq!(inflate(&packed, 10000).unwrap(), zeros);
+            assert_eq!(inflate(&packed, 9999).unwrap_err().to_string(), "gzip data inflates to more than 9999 bytes");
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use wasm_bindgen::prelude::*;
+use std::mem;
+use rng::Rng;
+use utils::vec_bytes;
+use error::Error;
+use universe::Universe;
+
+//Lenia: every cell holds a value in [0, 1], each tick the values are convolved with a smooth
+//ring shaped kernel and grow or shrink by how close that potential is to mu
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct ContinuousUniverse {
+    width: u32,
+    height: u32,
+    cells: Vec<f32>,
+    next: Vec<f32>,
+    generation: u32,
+    radius: u32,
+    //Nonzero kernel weights as (row offset, column offset, weight), the weights sum to 1
+    kernel: Vec<(i32, i32, f32)>,
+    mu: f32,
+    sigma: f32,
+    dt: f32,
+}
+
+pub const DEFAULT_RADIUS: u32 = 13;
+//Smallest radius whose ring has any cells, at radius 1 every neighbour sits on the edge
+pub const MIN_RADIUS: u32 = 2;
+pub const MAX_RADIUS: u32 = 32;
+
+//Ring kernel of the given radius, peaking halfway out from the center
+fn ring_kernel(radius: u32) -> Vec<(i32, i32, f32)> {
+    let r = radius as i32;
+    let mut kernel: Vec<(i32, i32, f32)> = iproduct!(-r..=r, -r..=r)
+        .filter_map(|(x, y)| {
+            let distance = ((x * x + y * y) as f32).sqrt() / radius as f32;
+            if distance > 0.0 && distance < 1.0 {
+                Some((x, y, (4.0 - 1.0 / (distance * (1.0 - distance))).exp()))
+            } else {
+                None
+            }
+        })
+        .collect();
+    let total: f32 = kernel.iter().map(|&(_, _, weight)| weight).sum();
+    for entry in kernel.iter_mut() {
+        entry.2 /= total;
+    }
+    kernel
+}
+
+//Bell shaped growth, 1 at mu and falling towards -1 further than a few sigma away
+fn growth(potential: f32, mu: f32, sigma: f32) -> f32 {
+    let x = (potential - mu) / sigma;
+    2.0 * (-x * x / 2.0).exp() - 1.0
+}
+
+#[wasm_bindgen]
+impl ContinuousUniverse {
+    //Empty universe with Orbium's parameters (radius 13, mu 0.15, sigma 0.015, dt 0.1). Throws
+    //for the sizes Universe.new does
+    pub fn new(width: u32, height: u32) -> Result<ContinuousUniverse, Error> {
+        Universe::check_size(width, height)?;
+        let size = (width * height) as usize;
+        Ok(ContinuousUniverse {width, height, cells: vec![0.0; size], next: Vec::with_capacity(size), generation: 0,
+            radius: DEFAULT_RADIUS, kernel: ring_kernel(DEFAULT_RADIUS), mu: 0.15, sigma: 0.015, dt: 0.1})
+    }
+
+    pub fn tick(&mut self) {
+        let (height, width) = (self.height as i32, self.width as i32);
+        self.next.clear();
+        for (row, col) in iproduct!(0..height, 0..width) {
+            let potential: f32 = self.kernel.iter()
+                .map(|&(x, y, weight)| {
+                    let idx = ((row + x).rem_euclid(height) * width + (col + y).rem_euclid(width)) as usize;
+                    self.cells[idx] * weight
+                })
+                .sum();
+            let value = self.cells[(row * width + col) as usize];
+            self.next.push((value + self.dt * growth(potential, self.mu, self.sigma)).clamp(0.0, 1.0));
+        }
+        ::std::mem::swap(&mut self.cells, &mut self.next);
+        self.generation += 1;
+    }
+
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+
+    //Fill a fraction density of the cells with random values, the same seed gives the same soup
+    pub fn randomize(&mut self, density: f64, seed: u64) {
+        let mut rng = Rng::new(seed);
+        for value in self.cells.iter_mut() {
+            *value = if rng.next_f64() < density { rng.next_f64() as f32 } else { 0.0 };
+        }
+        self.generation = 0;
+    }
+
+    //Growth center, width and time step. dt is clamped to (0, 1], sigma must be positive
+    pub fn set_params(&mut self, mu: f32, sigma: f32, dt: f32) {
+        self.mu = mu;
+        self.sigma = sigma.max(f32::EPSILON);
+        self.dt = dt.clamp(f32::EPSILON, 1.0);
+    }
+
+    //Kernel radius in cells, clamped to MIN_RADIUS..=MAX_RADIUS
+    pub fn set_radius(&mut self, radius: u32) {
+        self.radius = radius.clamp(MIN_RADIUS, MAX_RADIUS);
+        self.kernel = ring_kernel(self.radius);
+    }
+
+    pub fn radius(&self) -> u32 {
+        self.radius
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    //width * height f32 values in [0, 1], row by row
+    pub fn cells(&self) -> *const f32 {
+        self.cells.as_ptr()
+    }
+
+    //Bytes taken by the value buffers and the kernel
+    pub fn memory_usage(&self) -> usize {
+        mem::size_of::<ContinuousUniverse>() + vec_bytes(&self.cells) + vec_bytes(&self.next) + vec_bytes(&self.kernel)
+    }
+
+    //Sum of all values
+    pub fn mass(&self) -> f32 {
+        self.cells.iter().sum()
+    }
+
+    pub fn get_cell(&self, row: u32, column: u32) -> f32 {
+        self.cells[self.get_index(row, column)]
+    }
+
+    //Values are clamped to [0, 1]
+    pub fn set_cell(&mut self, row: u32, column: u32, value: f32) {
+        let idx = self.get_index(row, column);
+        self.cells[idx] = value.clamp(0.0, 1.0);
+    }
+
+    pub fn clear(&mut self) {
+        for value in self.cells.iter_mut() {
+            *value = 0.0;
+        }
+        self.generation = 0;
+    }
+
+    fn get_index(&self, row: u32, column: u32) -> usize {
+        ((row % self.height) * self.width + column % self.width) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_normalize_ring_kernel() {
+        let kernel = ring_kernel(5);
+        let total: f32 = kernel.iter().map(|&(_, _, weight)| weight).sum();
+        assert!((total - 1.0).abs() < 1e-5);
+        assert!(kernel.iter().all(|&(x, y, _)| (x, y) != (0, 0)));
+
+        let mut univ = ContinuousUniverse::new(8, 8).unwrap();
+        univ.set_radius(1);
+        assert_eq!(univ.radius(), MIN_RADIUS);
+        let total: f32 = univ.kernel.iter().map(|&(_, _, weight)| weight).sum();
+        assert!((total - 1.0).abs() < 1e-5);
+        assert!(ContinuousUniverse::new(70000, 70000).is_err());
+    }
+
+    #[test]
+    fn should_decay_empty_and_full_worlds() {
+        let mut univ = ContinuousUniverse::new(32, 32).unwrap();
+        univ.set_radius(4);
+        univ.tick();
+        assert_eq!(univ.mass(), 0.0);
+
+        for (row, col) in iproduct!(0..32, 0..32) {
+            univ.set_cell(row, col, 1.0);
+        }
+        univ.tick_n(5);
+        assert!(univ.mass() < 32.0 * 32.0);
+        assert_eq!(univ.generation(), 6);
+    }
+
+    #[test]
+    fn should_grow_near_mu() {
+        let mut univ = ContinuousUniverse::new(16, 16).unwrap();
+        univ.set_radius(3);
+        univ.set_params(0.5, 0.1, 0.1);
+        for (row, col) in iproduct!(0..16, 0..16) {
+            univ.set_cell(row, col, 0.5);
+        }
+        univ.tick();
+        assert!((univ.get_cell(3, 3) - 0.6).abs() < 1e-5);
+        univ.set_cell(0, 0, 2.0);
+        assert_eq!(univ.get_cell(0, 0), 1.0);
+    }
+}
@@ -0,0 +1,55 @@
+//Errors of the checked API. Methods exported to JS return Result<_, Error> and wasm-bindgen turns
+//an Err into a thrown JS Error (a JsError) carrying the message, so bad input from JS no longer
+//panics and poisons the wasm instance
+
+use std::error;
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    //Cell coordinates past the edge of a width x height board
+    OutOfBounds { row: u32, column: u32, width: u32, height: u32 },
+    //Text that isn't the format it claims to be, line counts from 1
+    Parse { format: &'static str, line: usize, message: String },
+    //Anything else, described by its message
+    Invalid(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::OutOfBounds { row, column, width, height } =>
+                write!(f, "cell ({}, {}) is outside the {}x{} board", row, column, width, height),
+            Error::Parse { format, line, ref message } => write!(f, "bad {} at line {}: {}", format, line, message),
+            Error::Invalid(ref message) => f.write_str(message),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl From<String> for Error {
+    fn from(message: String) -> Error {
+        Error::Invalid(message)
+    }
+}
+
+impl From<Error> for JsValue {
+    fn from(error: Error) -> JsValue {
+        JsError::new(&error.to_string()).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_describe_errors() {
+        let outside = Error::OutOfBounds { row: 9, column: 2, width: 8, height: 8 };
+        assert_eq!(outside.to_string(), "cell (9, 2) is outside the 8x8 board");
+        let rle = Error::Parse { format: "RLE", line: 3, message: "unexpected q".to_string() };
+        assert_eq!(rle.to_string(), "bad RLE at line 3: unexpected q");
+    }
+}
@@ -0,0 +1,278 @@
+//Pattern file formats and apgcodes. Each format reads text into a PatternFile and writes one back
+//(RLE is written by Universe::to_rle), Universe gets from_/to_ methods for JS on top
+
+use wasm_bindgen::prelude::*;
+use std::convert::TryFrom;
+use std::str;
+use compress::maybe_gunzip;
+use error::Error;
+use mode::Mode;
+use rule::Rule;
+use universe::{Cell, Universe};
+
+mod apgcode;
+mod life;
+mod plaintext;
+mod rle;
+#[cfg(feature = "fetch")]
+mod fetch;
+
+pub use self::apgcode::{apgcode, decode_wechsler, encode_wechsler};
+pub use self::life::{parse_life105, parse_life106, write_life105, write_life106};
+pub use self::plaintext::{parse_plaintext, write_plaintext};
+pub use self::rle::parse_rle;
+
+//Metadata pattern files carry besides their cells. Readers fill in what their format has and
+//writers put back what it can hold, so attribution survives a round trip
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PatternInfo {
+    #[wasm_bindgen(skip)]
+    pub name: Option<String>,
+    #[wasm_bindgen(skip)]
+    pub author: Option<String>,
+    //Comment lines in order
+    #[wasm_bindgen(skip)]
+    pub comments: Vec<String>,
+    //None for whatever rule the reader uses by default, Conway's for most formats
+    #[wasm_bindgen(skip)]
+    pub rule: Option<Rule>,
+}
+
+#[wasm_bindgen]
+impl PatternInfo {
+    pub fn new() -> PatternInfo {
+        PatternInfo::default()
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
+    pub fn author(&self) -> Option<String> {
+        self.author.clone()
+    }
+
+    pub fn set_author(&mut self, author: Option<String>) {
+        self.author = author;
+    }
+
+    pub fn comments(&self) -> Vec<String> {
+        self.comments.clone()
+    }
+
+    pub fn set_comments(&mut self, comments: Vec<String>) {
+        self.comments = comments;
+    }
+
+    //B/S notation
+    pub fn rule(&self) -> Option<String> {
+        self.rule.map(|rule| rule.to_string())
+    }
+
+    pub fn set_rule(&mut self, rule: Option<String>) -> Result<(), Error> {
+        self.rule = match rule {
+            Some(rule) => Some(rule.parse::<Rule>()?),
+            None => None,
+        };
+        Ok(())
+    }
+}
+
+//Live cells of a pattern relative to the top-left of their bounding box, with the metadata
+//formats carry. JS gets one from the parse_ functions to read the metadata
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PatternFile {
+    #[wasm_bindgen(skip)]
+    pub info: PatternInfo,
+    //(row, column) of every live cell
+    #[wasm_bindgen(skip)]
+    pub cells: Vec<(u32, u32)>,
+}
+
+#[wasm_bindgen]
+impl PatternFile {
+    pub fn info(&self) -> PatternInfo {
+        self.info.clone()
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.info.name()
+    }
+
+    pub fn author(&self) -> Option<String> {
+        self.info.author()
+    }
+
+    pub fn comments(&self) -> Vec<String> {
+        self.info.comments()
+    }
+
+    pub fn rule(&self) -> Option<String> {
+        self.info.rule()
+    }
+
+    pub fn width(&self) -> u32 {
+        self.cells.iter().map(|&(_, col)| col + 1).max().unwrap_or(0)
+    }
+
+    pub fn height(&self) -> u32 {
+        self.cells.iter().map(|&(row, _)| row + 1).max().unwrap_or(0)
+    }
+
+    //Universe just big enough for the pattern, a single dead cell for an empty one. Patterns
+    //too big for a universe (see Universe::check_size) are an error
+    pub fn to_universe(&self) -> Result<Universe, Error> {
+        let (width, height) = (self.width().max(1), self.height().max(1));
+        Universe::check_size(width, height)?;
+        let mut universe = Universe::empty(width, height);
+        if let Some(rule) = self.info.rule {
+            universe.set_rule(rule);
+        }
+        universe.insert_pattern(self, 0, 0);
+        Ok(universe)
+    }
+}
+
+impl PatternFile {
+    //Cells from coordinates that may be negative, moved so the smallest row and column are 0.
+    //Errors when they spread further than a u32 (less one, so width and height fit too) reaches
+    pub fn from_coords(coords: &[(i64, i64)]) -> Result<PatternFile, Error> {
+        let top = coords.iter().map(|&(row, _)| row).min().unwrap_or(0);
+        let left = coords.iter().map(|&(_, col)| col).min().unwrap_or(0);
+        let offset = |value: i64, start: i64| value.checked_sub(start)
+            .and_then(|offset| u32::try_from(offset).ok())
+            .filter(|&offset| offset < u32::MAX)
+            .ok_or_else(|| Error::Invalid(format!("pattern cells are more than {} apart", u32::MAX - 1)));
+        let mut cells = coords.iter().map(|&(row, col)| Ok((offset(row, top)?, offset(col, left)?))).collect::<Result<Vec<_>, Error>>()?;
+        cells.sort_unstable();
+        cells.dedup();
+        Ok(PatternFile { cells, ..PatternFile::default() })
+    }
+
+    //Live cells of the universe cropped to their bounding box, with the rule in Life mode
+    pub fn from_universe(universe: &Universe) -> PatternFile {
+        let coords: Vec<(i64, i64)> = universe.live_cells().chunks(2).map(|cell| (i64::from(cell[0]), i64::from(cell[1]))).collect();
+        let rule = if universe.mode() == Mode::Life { Some(universe.rule()) } else { None };
+        PatternFile { info: PatternInfo { rule, ..PatternInfo::default() }, ..PatternFile::from_coords(&coords).expect("a universe fits a pattern") }
+    }
+
+    //Bounding box row by row, true for live cells
+    pub fn rows(&self) -> Vec<Vec<bool>> {
+        let mut rows = vec![vec![false; self.width() as usize]; self.height() as usize];
+        for &(row, col) in &self.cells {
+            rows[row as usize][col as usize] = true;
+        }
+        rows
+    }
+}
+
+//Read text in whichever format it looks like: RLE, plaintext .cells or Life 1.05/1.06
+pub fn parse_pattern(text: &str) -> Result<PatternFile, Error> {
+    let text = text.trim_start();
+    let first = text.lines().find(|line| !line.starts_with('#')).unwrap_or("").trim();
+    if text.starts_with("#Life 1.05") {
+        parse_life105(text)
+    } else if text.starts_with("#Life 1.06") {
+        parse_life106(text)
+    } else if text.starts_with("[M2]") {
+        Err(Error::Invalid("macrocell patterns only fit an InfiniteUniverse, see InfiniteUniverse::from_macrocell".to_string()))
+    } else if text.starts_with('!') || first.chars().all(|c| c == '.' || c == 'O' || c == '*') {
+        parse_plaintext(text)
+    } else {
+        parse_rle(text)
+    }
+}
+
+//parse_pattern of a file's bytes, which may be gzip compressed (.rle.gz and the like)
+pub fn parse_pattern_bytes(bytes: &[u8]) -> Result<PatternFile, Error> {
+    let bytes = maybe_gunzip(bytes)?;
+    parse_pattern(str::from_utf8(&bytes).map_err(|_| Error::Invalid("pattern file isn't UTF-8 text".to_string()))?)
+}
+
+//Pattern and metadata of a file in any format parse_pattern knows, straight from a Uint8Array
+#[wasm_bindgen(js_name = parse_pattern_bytes)]
+pub fn parse_pattern_bytes_js(bytes: &[u8]) -> Result<PatternFile, Error> {
+    parse_pattern_bytes(bytes)
+}
+
+#[wasm_bindgen]
+impl Universe {
+    //Set the pattern's live cells with its top-left at (row, column), wrapping around the edges.
+    //Its rule is left out
+    pub fn insert_pattern(&mut self, pattern: &PatternFile, row: u32, column: u32) {
+        for &(r, c) in &pattern.cells {
+            self.set_cell(row + r, column + c, Cell::Alive);
+        }
+    }
+}
+
+//Rule as "survival/birth" digits like Life 1.05 and old pattern collections write it, None for
+//rules that aren't two state outer totalistic Moore rules
+pub fn survival_birth(rule: &Rule) -> Option<String> {
+    let text = rule.to_string();
+    let (birth, survival) = text.strip_prefix('B')?.split_once("/S")?;
+    if birth.chars().chain(survival.chars()).all(|c| c.is_ascii_digit()) {
+        Some(format!("{}/{}", survival, birth))
+    } else {
+        None
+    }
+}
+
+//Reverse of survival_birth
+pub fn parse_survival_birth(text: &str) -> Result<Rule, Error> {
+    let mut parts = text.trim().split('/');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(survival), Some(birth), None) => format!("B{}/S{}", birth.trim(), survival.trim()).parse(),
+        _ => Err(Error::Invalid(format!("invalid survival/birth rule {}", text))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rule::RulePreset;
+
+    #[test]
+    fn should_normalise_coordinates() {
+        let pattern = PatternFile::from_coords(&[(-1, 2), (0, -3), (-1, 2)]).unwrap();
+        assert_eq!(pattern.cells, vec![(0, 5), (1, 0)]);
+        assert_eq!((pattern.width(), pattern.height()), (6, 2));
+        let univ = pattern.to_universe().unwrap();
+        assert_eq!(PatternFile::from_universe(&univ), PatternFile { info: PatternInfo { rule: Some(Rule::conway()), ..PatternInfo::default() }, ..pattern });
+        assert!(PatternFile::from_coords(&[(i64::MIN, 0), (i64::MAX, 0)]).is_err());
+        assert!(PatternFile::from_coords(&[(0, 0), (0, i64::from(u32::MAX))]).is_err());
+        assert!(PatternFile::from_coords(&[(0, 0), (100000, 100000)]).unwrap().to_universe().is_err());
+
+        assert_eq!(survival_birth(&Rule::from(RulePreset::HighLife)), Some("23/36".to_string()));
+        assert_eq!(parse_survival_birth("23/36"), Ok(Rule::from(RulePreset::HighLife)));
+        assert_eq!(survival_birth(&Rule::generations(&[2], &[], 3)), None);
+    }
+
+    #[test]
+    fn should_sniff_pattern_formats() {
+        let glider = vec![(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)];
+        for text in ["x = 3, y = 3\nbo$2bo$3o!", "#C glider\nbo$2bo$3o!", ".O.\n..O\nOOO", "!Name: Glider\n.O.\n..O\nOOO",
+                     "#Life 1.06\n1 0\n2 1\n0 2\n1 2\n2 2", "#Life 1.05\n#P 0 0\n.*\n..*\n***"].iter() {
+            assert_eq!(parse_pattern(text).map(|pattern| pattern.cells), Ok(glider.clone()), "{}", text);
+        }
+        assert!(parse_pattern("[M2] (golly 4.0)\n$$..*$\n").is_err());
+        //Files describing more cells than a universe holds
+        assert!(Universe::from_rle("x = 0, y = 0\n70000o70000$o!").is_err());
+        assert!(Universe::from_life106("#Life 1.06\n0 0\n100000 100000").is_err());
+        assert!(Universe::from_life105("#Life 1.05\n#P 0 0\n*\n#P 100000 100000\n*").is_err());
+        assert!(Universe::from_plaintext(&format!("{}O\n{}O", ".".repeat(70000), ".\n".repeat(1000))).is_err());
+        assert_eq!(parse_pattern_bytes(b"bo$2bo$3o!").map(|pattern| pattern.cells), Ok(glider.clone()));
+        #[cfg(feature = "compress")]
+        assert_eq!(parse_pattern_bytes(&::compress::gzip(b"bo$2bo$3o!")).map(|pattern| pattern.cells), Ok(glider.clone()));
+
+        let mut univ = Universe::empty(4, 4);
+        univ.insert_pattern(&parse_pattern(".O.\n..O\nOOO").unwrap(), 2, 2);
+        assert_eq!(univ.live_cells(), vec![0, 0, 0, 2, 0, 3, 2, 3, 3, 0]);
+    }
+}
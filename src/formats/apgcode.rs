@@ -0,0 +1,204 @@
+use wasm_bindgen::prelude::*;
+use error::Error;
+use infinite::InfiniteUniverse;
+use mode::Mode;
+use pattern::Transform;
+use rule::Rule;
+use universe::{Cell, Universe};
+use super::PatternFile;
+
+const TRANSFORMS: [Transform; 8] = [
+    Transform::Identity, Transform::Rotate90, Transform::Rotate180, Transform::Rotate270,
+    Transform::FlipHorizontal, Transform::FlipVertical, Transform::FlipDiagonal, Transform::FlipAntiDiagonal,
+];
+
+//Extended Wechsler body of the pattern as it lies: strips of 5 rows separated by z, each column
+//one base 32 digit with the top row as bit 0. Runs of empty columns shrink to w (2), x (3) or
+//y and a digit (4 to 39), empty columns at the end of a strip are left out
+pub fn encode_wechsler(pattern: &PatternFile) -> String {
+    if pattern.cells.is_empty() {
+        return "0".to_string();
+    }
+    let width = pattern.width() as usize;
+    let strips: Vec<String> = pattern.rows().chunks(5).map(|strip| {
+        let (mut text, mut zeros) = (String::new(), 0);
+        for col in 0..width {
+            let value = strip.iter().enumerate().filter(|(_, row)| row[col]).fold(0, |value, (bit, _)| value | 1 << bit);
+            if value == 0 {
+                zeros += 1;
+                continue;
+            }
+            push_zeros(&mut text, zeros);
+            zeros = 0;
+            text.push(digit(value));
+        }
+        text
+    }).collect();
+    strips.join("z")
+}
+
+fn digit(value: usize) -> char {
+    std::char::from_digit(value as u32, 36).unwrap()
+}
+
+fn push_zeros(text: &mut String, mut zeros: usize) {
+    while zeros > 0 {
+        let run = zeros.min(39);
+        match run {
+            1 => text.push('0'),
+            2 => text.push('w'),
+            3 => text.push('x'),
+            _ => {
+                text.push('y');
+                text.push(digit(run - 4));
+            }
+        }
+        zeros -= run;
+    }
+}
+
+//Cells of a Wechsler body, with or without the xs4_ style prefix. Codes are a single line
+pub fn decode_wechsler(code: &str) -> Result<PatternFile, Error> {
+    let error = |message| Error::Parse { format: "apgcode", line: 1, message };
+    let body = match code.split_once('_') {
+        Some((prefix, body)) if is_prefix(prefix) => body,
+        Some(_) => return Err(error(format!("unsupported apgcode {}", code))),
+        None => code,
+    };
+    let (mut coords, mut strip, mut col) = (Vec::new(), 0, 0);
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            'z' => {
+                strip += 5;
+                col = 0;
+            }
+            'w' => col += 2,
+            'x' => col += 3,
+            'y' => col += 4 + chars.next().and_then(|c| c.to_digit(36)).ok_or_else(|| error("apgcode ends after y".to_string()))? as i64,
+            _ => {
+                let value = c.to_digit(32).ok_or_else(|| error(format!("unexpected {} in apgcode", c)))?;
+                coords.extend((0..5).filter(|bit| value >> bit & 1 == 1).map(|bit| (strip + i64::from(bit), col)));
+                col += 1;
+            }
+        }
+    }
+    PatternFile::from_coords(&coords)
+}
+
+//xs, xp or xq and a number
+fn is_prefix(prefix: &str) -> bool {
+    let kind = prefix.strip_prefix("xs").or_else(|| prefix.strip_prefix("xp")).or_else(|| prefix.strip_prefix("xq"));
+    kind.is_some_and(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+}
+
+//Shortest code, then the first alphabetically, of the eight orientations
+fn canonical_wechsler(phase: &PatternFile) -> String {
+    let (height, width) = (phase.height(), phase.width());
+    TRANSFORMS.iter()
+        .map(|&transform| {
+            let coords: Vec<(i64, i64)> = phase.cells.iter().map(|&(row, col)| {
+                let (row, col) = transform.apply(row, col, height, width);
+                (i64::from(row), i64::from(col))
+            }).collect();
+            encode_wechsler(&PatternFile::from_coords(&coords).expect("a transformed pattern is as big as the pattern"))
+        })
+        .min_by(|a, b| (a.len(), a).cmp(&(b.len(), b)))
+        .unwrap()
+}
+
+//Catagolue's name for a still life (xs and the population), oscillator (xp and the period) or
+//spaceship (xq and the period). The pattern runs on an unbounded plane under its rule, Conway's
+//when it has none, until it comes back to its first phase; patterns that die out or don't within
+//max_period generations have no apgcode
+pub fn apgcode(pattern: &PatternFile, max_period: u32) -> Result<String, Error> {
+    let mut plane = InfiniteUniverse::new();
+    plane.set_rule(pattern.info.rule.unwrap_or_else(Rule::conway));
+    for &(row, col) in &pattern.cells {
+        plane.set_cell(row as i32, col as i32, Cell::Alive);
+    }
+    let (first, corner) = phase(&plane)?;
+    let mut phases = vec![first];
+    for period in 1..=max_period {
+        plane.tick();
+        let (now, moved_to) = phase(&plane)?;
+        if now.cells != phases[0].cells {
+            phases.push(now);
+            continue;
+        }
+        let prefix = match (moved_to != corner, period) {
+            (true, _) => format!("xq{}", period),
+            (false, 1) => format!("xs{}", now.cells.len()),
+            (false, _) => format!("xp{}", period),
+        };
+        let body = phases.iter().map(canonical_wechsler).min_by(|a, b| (a.len(), a).cmp(&(b.len(), b))).unwrap();
+        return Ok(format!("{}_{}", prefix, body));
+    }
+    Err(Error::Invalid(format!("pattern doesn't repeat within {} generations", max_period)))
+}
+
+//Live cells of the plane normalised, and where their bounding box starts
+fn phase(plane: &InfiniteUniverse) -> Result<(PatternFile, (i64, i64)), Error> {
+    let coords: Vec<(i64, i64)> = plane.live_cells().chunks(2).map(|cell| (i64::from(cell[0]), i64::from(cell[1]))).collect();
+    if coords.is_empty() {
+        return Err(Error::Invalid("pattern dies out".to_string()));
+    }
+    let top = coords.iter().map(|&(row, _)| row).min().unwrap();
+    let left = coords.iter().map(|&(_, col)| col).min().unwrap();
+    Ok((PatternFile::from_coords(&coords)?, (top, left)))
+}
+
+#[wasm_bindgen]
+impl Universe {
+    //Universe just big enough for the object an apgcode names, under Conway's rule
+    pub fn from_apgcode(code: &str) -> Result<Universe, Error> {
+        decode_wechsler(code).and_then(|pattern| pattern.to_universe())
+    }
+
+    //apgcode of the live cells as a single object, only for two state Life rules. The board's
+    //edges play no part, the cells run on an unbounded plane
+    #[wasm_bindgen(js_name = apgcode)]
+    pub fn apgcode_js(&self, max_period: u32) -> Result<String, Error> {
+        self.apgcode(max_period)
+    }
+}
+
+impl Universe {
+    pub fn apgcode(&self, max_period: u32) -> Result<String, Error> {
+        if self.mode() != Mode::Life {
+            return Err(Error::Invalid("apgcodes need a Life mode universe".to_string()));
+        }
+        apgcode(&PatternFile::from_universe(self), max_period)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(cells: &[(i64, i64)]) -> Result<String, Error> {
+        apgcode(&PatternFile::from_coords(cells)?, 100)
+    }
+
+    #[test]
+    fn should_name_small_objects() {
+        assert_eq!(code(&[(0, 0), (0, 1), (1, 0), (1, 1)]), Ok("xs4_33".to_string()));
+        assert_eq!(code(&[(0, 1), (0, 2), (1, 0), (1, 3), (2, 1), (2, 2)]), Ok("xs6_696".to_string()));
+        assert_eq!(code(&[(5, 0), (5, 1), (5, 2)]), Ok("xp2_7".to_string()));
+        assert_eq!(code(&[(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]), Ok("xq4_153".to_string()));
+        assert!(code(&[(0, 0)]).is_err());
+
+        let beehive = decode_wechsler("xs6_696").unwrap();
+        assert_eq!(beehive.cells, vec![(0, 1), (1, 0), (1, 2), (2, 0), (2, 2), (3, 1)]);
+        assert!(decode_wechsler("xz9_33").is_err());
+    }
+
+    #[test]
+    fn should_round_trip_wechsler() {
+        //Two strips and a run of 45 empty columns
+        let pattern = PatternFile::from_coords(&[(0, 0), (4, 1), (6, 0), (6, 46)]).unwrap();
+        let text = encode_wechsler(&pattern);
+        assert_eq!(text, "1gz2yzy22");
+        assert_eq!(decode_wechsler(&text), Ok(pattern));
+    }
+}
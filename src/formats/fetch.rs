@@ -0,0 +1,70 @@
+use futures::{future, Future};
+use js_sys::{Function, Promise, Reflect, Uint8Array};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use web_sys::Response;
+use error::Error;
+use universe::Universe;
+use super::parse_pattern_bytes;
+
+//Error for text that came back as a web page instead of a pattern, e.g. a wiki article's URL
+//rather than its raw file
+fn check_content_type(content_type: &str) -> Result<(), Error> {
+    if content_type.contains("html") {
+        return Err(Error::Invalid(format!("expected a pattern file but got {}", content_type)));
+    }
+    Ok(())
+}
+
+#[wasm_bindgen]
+impl Universe {
+    //Download an RLE, .cells or Life 1.05/1.06 file, gzip compressed or not, and resolve to its
+    //PatternFile. The format is told from the contents. Network failures, error statuses and
+    //unreadable files reject with a JS Error
+    pub fn load_pattern_from_url(url: &str) -> Promise {
+        let request = web_sys::window()
+            .ok_or_else(|| Error::Invalid("no window to fetch from".to_string()).into())
+            .map(|window| window.fetch_with_str(url));
+        let download = future::result(request)
+            .and_then(JsFuture::from)
+            .and_then(|response| {
+                let response: Response = response.dyn_into()?;
+                if !response.ok() {
+                    let message = format!("{} answered {} {}", response.url(), response.status(), response.status_text());
+                    return Err(Error::Invalid(message).into());
+                }
+                let content_type = response.headers().get("content-type")?.unwrap_or_default();
+                Ok(JsFuture::from(response.array_buffer()?).map(move |buffer| (buffer, content_type)))
+            })
+            .flatten();
+        future_to_promise(download.and_then(|(buffer, content_type)| {
+            check_content_type(&content_type)
+                .and_then(|_| parse_pattern_bytes(&Uint8Array::new(&buffer).to_vec()))
+                .map(JsValue::from)
+                .map_err(JsValue::from)
+        }))
+    }
+
+    //Download a pattern as load_pattern_from_url does and insert it with its top-left at (row,
+    //column): await Universe.insert_pattern_from_url(universe, url, row, column). The universe is
+    //the JS object, called through its insert_pattern once the file is in, so it stays usable
+    //while the request runs (a Rust borrow couldn't be held that long). Resolves to undefined
+    pub fn insert_pattern_from_url(universe: JsValue, url: &str, row: u32, column: u32) -> Promise {
+        future_to_promise(JsFuture::from(Universe::load_pattern_from_url(url)).and_then(move |pattern| {
+            let insert: Function = Reflect::get(&universe, &"insert_pattern".into())?.dyn_into()?;
+            insert.call3(&universe, &pattern, &row.into(), &column.into())
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_turn_down_web_pages() {
+        assert!(check_content_type("text/html; charset=utf-8").is_err());
+        assert!(check_content_type("text/plain").is_ok());
+    }
+}
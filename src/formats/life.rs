@@ -0,0 +1,149 @@
+use wasm_bindgen::prelude::*;
+use error::Error;
+use universe::Universe;
+use super::{parse_survival_birth, survival_birth, PatternFile, PatternInfo};
+
+//Life 1.05: #D comment lines, #N for Conway's rules or #R survival/birth, then blocks of
+//. and * rows each starting with #P and the column and row of its top-left cell
+pub fn parse_life105(text: &str) -> Result<PatternFile, Error> {
+    let error = |line, message: String| Error::Parse { format: "Life 1.05", line, message };
+    let mut lines = text.lines().map(str::trim_end).enumerate().map(|(number, line)| (number + 1, line));
+    if !lines.next().is_some_and(|(_, header)| header.starts_with("#Life 1.05")) {
+        return Err(error(1, "missing #Life 1.05 header".to_string()));
+    }
+    let (mut comments, mut rule, mut coords) = (Vec::new(), None, Vec::new());
+    let mut block: Option<(i64, i64)> = None;
+    for (number, line) in lines {
+        if let Some(text) = line.strip_prefix("#D") {
+            comments.push(text.trim().to_string());
+        } else if line.starts_with("#N") {
+            rule = None;
+        } else if let Some(text) = line.strip_prefix("#R") {
+            rule = Some(parse_survival_birth(text).map_err(|e| error(number, e.to_string()))?);
+        } else if let Some(position) = line.strip_prefix("#P") {
+            let numbers = position.split_whitespace().map(|n| n.parse::<i64>()).collect::<Result<Vec<_>, _>>();
+            block = match numbers.as_ref().map(Vec::as_slice) {
+                Ok([column, row]) => Some((*row, *column)),
+                _ => return Err(error(number, format!("invalid block position {}", line))),
+            };
+        } else if line.starts_with('#') || line.is_empty() {
+            continue;
+        } else {
+            let (row, column) = block.ok_or_else(|| error(number, "cells before the first #P".to_string()))?;
+            for (col, c) in line.chars().enumerate() {
+                match c {
+                    '*' => coords.push((row, column + col as i64)),
+                    '.' => {}
+                    _ => return Err(error(number, format!("unexpected {} in cell row", c))),
+                }
+            }
+            block = Some((row + 1, column));
+        }
+    }
+    Ok(PatternFile { info: PatternInfo { comments, rule, ..PatternInfo::default() }, ..PatternFile::from_coords(&coords)? })
+}
+
+//One #P block over the bounding box, centred on the origin as most Life 1.05 files are
+pub fn write_life105(pattern: &PatternFile) -> String {
+    let mut text = "#Life 1.05\n".to_string();
+    for line in &pattern.info.comments {
+        text.push_str(&format!("#D {}\n", line));
+    }
+    match pattern.info.rule.as_ref().and_then(survival_birth) {
+        Some(ref rule) if rule != "23/3" => text.push_str(&format!("#R {}\n", rule)),
+        _ => text.push_str("#N\n"),
+    }
+    text.push_str(&format!("#P {} {}\n", -(pattern.width() as i64 / 2), -(pattern.height() as i64 / 2)));
+    for row in pattern.rows() {
+        let used = row.iter().rposition(|&alive| alive).map_or(0, |last| last + 1);
+        let line: String = row[..used].iter().map(|&alive| if alive { '*' } else { '.' }).collect();
+        //A lone . keeps empty rows from looking like the end of the block
+        text.push_str(if line.is_empty() { "." } else { &line });
+        text.push('\n');
+    }
+    text
+}
+
+//Life 1.06: one "column row" pair per live cell after the header
+pub fn parse_life106(text: &str) -> Result<PatternFile, Error> {
+    let error = |line, message: String| Error::Parse { format: "Life 1.06", line, message };
+    let mut lines = text.lines().map(str::trim).enumerate().map(|(number, line)| (number + 1, line));
+    if !lines.next().is_some_and(|(_, header)| header.starts_with("#Life 1.06")) {
+        return Err(error(1, "missing #Life 1.06 header".to_string()));
+    }
+    let (mut comments, mut coords) = (Vec::new(), Vec::new());
+    for (number, line) in lines {
+        if let Some(text) = line.strip_prefix("#D") {
+            comments.push(text.trim().to_string());
+        } else if line.starts_with('#') || line.is_empty() {
+            continue;
+        } else {
+            let numbers = line.split_whitespace().map(|n| n.parse::<i64>()).collect::<Result<Vec<_>, _>>();
+            match numbers.as_ref().map(Vec::as_slice) {
+                Ok([column, row]) => coords.push((*row, *column)),
+                _ => return Err(error(number, format!("invalid cell {}", line))),
+            }
+        }
+    }
+    Ok(PatternFile { info: PatternInfo { comments, ..PatternInfo::default() }, ..PatternFile::from_coords(&coords)? })
+}
+
+//Cells relative to the top-left of the bounding box. Life 1.06 has no rules or descriptions
+pub fn write_life106(pattern: &PatternFile) -> String {
+    let mut text = "#Life 1.06\n".to_string();
+    for &(row, col) in &pattern.cells {
+        text.push_str(&format!("{} {}\n", col, row));
+    }
+    text
+}
+
+#[wasm_bindgen]
+impl Universe {
+    //Universe just big enough for a Life 1.05 pattern, with its rule
+    pub fn from_life105(text: &str) -> Result<Universe, Error> {
+        parse_life105(text).and_then(|pattern| pattern.to_universe())
+    }
+
+    //Live cells cropped to their bounding box as Life 1.05
+    pub fn to_life105(&self) -> String {
+        write_life105(&PatternFile::from_universe(self))
+    }
+
+    pub fn from_life106(text: &str) -> Result<Universe, Error> {
+        parse_life106(text).and_then(|pattern| pattern.to_universe())
+    }
+
+    pub fn to_life106(&self) -> String {
+        write_life106(&PatternFile::from_universe(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rule::{Rule, RulePreset};
+
+    fn glider() -> PatternFile {
+        PatternFile::from_coords(&[(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]).unwrap()
+    }
+
+    #[test]
+    fn should_read_and_write_life105() {
+        let text = "#Life 1.05\n#D Glider\n#R 23/36\n#P -1 -1\n.*\n..*\n***\n";
+        let pattern = parse_life105(text).unwrap();
+        assert_eq!(pattern, PatternFile { info: PatternInfo { comments: vec!["Glider".to_string()], rule: Some(Rule::from(RulePreset::HighLife)), ..PatternInfo::default() }, ..glider() });
+        assert_eq!(write_life105(&pattern), text);
+        assert_eq!(parse_life105("#Life 1.05\n#P 0 0\n*\n#P 5 -2\n.*\n").unwrap().cells, vec![(0, 6), (2, 0)]);
+        assert_eq!(parse_life105("#Life 1.05\n#D\n*\n").unwrap_err().to_string(), "bad Life 1.05 at line 3: cells before the first #P");
+        assert!(parse_life105("x = 1").is_err());
+    }
+
+    #[test]
+    fn should_read_and_write_life106() {
+        let pattern = parse_life106("#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n").unwrap();
+        assert_eq!(pattern, glider());
+        assert_eq!(write_life106(&pattern), "#Life 1.06\n1 0\n2 1\n0 2\n1 2\n2 2\n");
+        assert_eq!(parse_life106("#Life 1.06\n1 two\n"), Err(Error::Parse { format: "Life 1.06", line: 2, message: "invalid cell 1 two".to_string() }));
+        assert_eq!(Universe::from_life106(&pattern.to_universe().unwrap().to_life106()).unwrap().live_cells(), pattern.to_universe().unwrap().live_cells());
+    }
+}
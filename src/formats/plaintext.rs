@@ -0,0 +1,90 @@
+use wasm_bindgen::prelude::*;
+use error::Error;
+use universe::Universe;
+use super::{PatternFile, PatternInfo};
+
+//Plaintext .cells: ! comment lines, usually starting with "!Name: .." and "!Author: ..", then one
+//line of . and O per row. Rows may stop early and empty lines are empty rows
+pub fn parse_plaintext(text: &str) -> Result<PatternFile, Error> {
+    let (mut info, mut coords) = (PatternInfo::default(), Vec::new());
+    let mut row = 0;
+    for (number, line) in text.lines().map(str::trim_end).enumerate() {
+        if let Some(comment) = line.strip_prefix('!') {
+            if let (Some(value), None) = (comment.strip_prefix("Name:"), &info.name) {
+                info.name = Some(value.trim().to_string());
+            } else if let (Some(value), None) = (comment.strip_prefix("Author:"), &info.author) {
+                info.author = Some(value.trim().to_string());
+            } else {
+                info.comments.push(comment.trim().to_string());
+            }
+            continue;
+        }
+        for (col, c) in line.chars().enumerate() {
+            match c {
+                'O' | '*' => coords.push((row, col as i64)),
+                '.' => {}
+                _ => return Err(Error::Parse { format: "plaintext", line: number + 1, message: format!("unexpected {} in row {}", c, row + 1) }),
+            }
+        }
+        row += 1;
+    }
+    Ok(PatternFile { info, ..PatternFile::from_coords(&coords)? })
+}
+
+//Full width rows so empty rows and columns survive
+pub fn write_plaintext(pattern: &PatternFile) -> String {
+    let mut text = String::new();
+    if let Some(ref name) = pattern.info.name {
+        text.push_str(&format!("!Name: {}\n", name));
+    }
+    if let Some(ref author) = pattern.info.author {
+        text.push_str(&format!("!Author: {}\n", author));
+    }
+    for line in &pattern.info.comments {
+        text.push_str(&format!("!{}\n", line));
+    }
+    for row in pattern.rows() {
+        text.extend(row.iter().map(|&alive| if alive { 'O' } else { '.' }));
+        text.push('\n');
+    }
+    text
+}
+
+//Pattern and metadata of a .cells file
+#[wasm_bindgen(js_name = parse_plaintext)]
+pub fn parse_plaintext_js(text: &str) -> Result<PatternFile, Error> {
+    parse_plaintext(text)
+}
+
+#[wasm_bindgen]
+impl Universe {
+    //Universe just big enough for a .cells pattern, use parse_plaintext for its name and comments
+    pub fn from_plaintext(text: &str) -> Result<Universe, Error> {
+        parse_plaintext(text).and_then(|pattern| pattern.to_universe())
+    }
+
+    //Live cells cropped to their bounding box as .cells, with the name, author and comments of
+    //info. Its rule is left out, .cells has no room for one
+    pub fn to_plaintext(&self, info: &PatternInfo) -> String {
+        write_plaintext(&PatternFile { info: info.clone(), ..PatternFile::from_universe(self) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_read_and_write_plaintext() {
+        let text = "!Name: Glider\n!Author: Richard K. Guy\n!The smallest spaceship\n!\n.O.\n..O\nOOO\n";
+        let pattern = parse_plaintext(text).unwrap();
+        assert_eq!((pattern.name(), pattern.author()), (Some("Glider".to_string()), Some("Richard K. Guy".to_string())));
+        assert_eq!(pattern.comments(), vec!["The smallest spaceship".to_string(), String::new()]);
+        assert_eq!(pattern.cells, vec![(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]);
+        assert_eq!(write_plaintext(&pattern), text);
+        assert_eq!(pattern.to_universe().unwrap().to_plaintext(&pattern.info), text);
+
+        assert_eq!(parse_plaintext("O\n\n.O").unwrap().cells, vec![(0, 0), (2, 1)]);
+        assert_eq!(parse_plaintext("!Name: x\nOxO").unwrap_err().to_string(), "bad plaintext at line 2: unexpected x in row 1");
+    }
+}
@@ -0,0 +1,110 @@
+use wasm_bindgen::prelude::*;
+use error::Error;
+use universe::{Universe, MAX_CELLS};
+use super::{PatternFile, PatternInfo};
+
+//Most live cells read from one file, 4M (64MB of coordinates) is far more than real patterns have
+const MAX_LIVE_CELLS: i64 = 1 << 22;
+
+//Two state RLE as Golly and LifeWiki write it: #N name, #O author and #C comment lines, an
+//x = .., y = .., rule = .. header (Conway's without one), then runs of b (dead), o (alive) and $
+//(end of row) up to a !. A run count in front repeats the tag, . and A are read as b and o. Runs
+//stop at MAX_CELLS, more than any universe can take, and the live cells at MAX_LIVE_CELLS
+pub fn parse_rle(text: &str) -> Result<PatternFile, Error> {
+    let (mut info, mut coords) = (PatternInfo::default(), Vec::new());
+    let (mut row, mut col, mut count) = (0i64, 0i64, 0i64);
+    let error = |line, message| Error::Parse { format: "RLE", line, message };
+    let mut lines = text.lines().map(str::trim).enumerate().filter(|(_, line)| !line.is_empty()).peekable();
+    while let Some(&(number, line)) = lines.peek() {
+        if let Some(comment) = line.strip_prefix('#') {
+            match comment.chars().next() {
+                Some('N') if info.name.is_none() => info.name = Some(comment[1..].trim().to_string()),
+                Some('O') if info.author.is_none() => info.author = Some(comment[1..].trim().to_string()),
+                Some('C') | Some('c') => info.comments.push(comment[1..].trim().to_string()),
+                _ => {}
+            }
+        } else if line.starts_with('x') {
+            let value = line.split(',').filter_map(|part| part.split_once('=')).find(|(key, _)| key.trim() == "rule");
+            if let Some((_, value)) = value {
+                info.rule = Some(value.trim().parse().map_err(|e: Error| error(number + 1, e.to_string()))?);
+            }
+        } else {
+            break;
+        }
+        lines.next();
+    }
+    'body: for (number, line) in lines {
+        for c in line.chars() {
+            if let Some(digit) = c.to_digit(10) {
+                count = count.checked_mul(10).map(|count| count + i64::from(digit))
+                    .filter(|&count| count <= i64::from(MAX_CELLS))
+                    .ok_or_else(|| error(number + 1, format!("run of more than {} cells", MAX_CELLS)))?;
+                continue;
+            }
+            if c.is_whitespace() {
+                continue;
+            }
+            let run = if count == 0 { 1 } else { count };
+            count = 0;
+            match c {
+                'b' | '.' => col += run,
+                'o' | 'A' => {
+                    if coords.len() as i64 + run > MAX_LIVE_CELLS {
+                        return Err(error(number + 1, format!("more than {} live cells", MAX_LIVE_CELLS)));
+                    }
+                    coords.extend((col..col + run).map(|c| (row, c)));
+                    col += run;
+                }
+                '$' => {
+                    row += run;
+                    col = 0;
+                }
+                '!' => break 'body,
+                _ => return Err(error(number + 1, format!("unexpected {}, only two state patterns can be read", c))),
+            }
+        }
+    }
+    Ok(PatternFile { info, ..PatternFile::from_coords(&coords)? })
+}
+
+//Pattern and metadata of an RLE file
+#[wasm_bindgen(js_name = parse_rle)]
+pub fn parse_rle_js(text: &str) -> Result<PatternFile, Error> {
+    parse_rle(text)
+}
+
+#[wasm_bindgen]
+impl Universe {
+    //Universe just big enough for an RLE pattern, under its rule
+    pub fn from_rle(text: &str) -> Result<Universe, Error> {
+        parse_rle(text).and_then(|pattern| pattern.to_universe())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rule::{Rule, RulePreset};
+
+    #[test]
+    fn should_read_rle() {
+        let text = "#N Glider\n#O Richard K. Guy\n#C The smallest spaceship\nx = 3, y = 3, rule = B36/S23\nbo$2bo$3o!";
+        let pattern = parse_rle(text).unwrap();
+        assert_eq!((pattern.name(), pattern.author()), (Some("Glider".to_string()), Some("Richard K. Guy".to_string())));
+        assert_eq!(pattern.comments(), vec!["The smallest spaceship".to_string()]);
+        assert_eq!(pattern.info.rule, Some(Rule::from(RulePreset::HighLife)));
+        assert_eq!(pattern.to_universe().unwrap().to_rle_with_info(&pattern.info), "#N Glider\n#O Richard K. Guy\n#C The smallest spaceship\nx = 3, y = 3, rule = B36/S23\nbo$2bo$3o!\n");
+        assert_eq!(pattern.cells, vec![(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]);
+
+        let univ = Universe::from_rle(&pattern.to_universe().unwrap().to_rle()).unwrap();
+        assert_eq!(PatternFile::from_universe(&univ).cells, pattern.cells);
+        assert_eq!(parse_rle("x = 0, y = 0\n12b\no2$1\n0o!").unwrap().cells, vec![(0, 12), (2, 0), (2, 1), (2, 2), (2, 3), (2, 4), (2, 5), (2, 6), (2, 7), (2, 8), (2, 9)]);
+        assert_eq!(parse_rle("#C two lines down\nx = 1, y = 1\n\n2B!").unwrap_err().to_string(),
+            "bad RLE at line 4: unexpected B, only two state patterns can be read");
+        assert_eq!(parse_rle("bo!").unwrap().cells, vec![(0, 0)]);
+        assert_eq!(parse_rle("x = 1, y = 1\n99999999999999999999o!").unwrap_err().to_string(),
+            format!("bad RLE at line 2: run of more than {} cells", MAX_CELLS));
+        assert_eq!(parse_rle(&format!("{}o$\n{}o!", MAX_LIVE_CELLS / 2, MAX_LIVE_CELLS / 2 + 1)).unwrap_err().to_string(),
+            format!("bad RLE at line 2: more than {} live cells", MAX_LIVE_CELLS));
+    }
+}
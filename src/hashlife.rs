@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+use rule::{Rule, MOORE_OFFSETS};
+use universe::Cell;
+use utils::{map_bytes, vec_bytes};
+
+mod macrocell;
+
+//Quadtree node. Level 0 nodes are single cells (ids 0 dead and 1 alive), a level k node is a
+//2^k square made of four level k - 1 quadrants in the order nw, ne, sw, se
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    level: u8,
+    children: [u32; 4],
+    population: u64,
+}
+
+const DEAD: u32 = 0;
+const ALIVE: u32 = 1;
+
+//HashLife: identical quadtree nodes are stored once, and the centre of every node advanced by
+//2^j generations is memoized, so repetitive patterns can be run exponentially far ahead
+#[derive(Debug)]
+pub struct HashLife {
+    nodes: Vec<Node>,
+    index: HashMap<[u32; 4], u32>,
+    results: HashMap<(u32, u8), u32>,
+    //Empty node of each level
+    empty: Vec<u32>,
+    root: u32,
+    //Row and column of the root's top left cell
+    origin: (i64, i64),
+    rule: Rule,
+}
+
+impl HashLife {
+    pub fn new(rule: Rule) -> HashLife {
+        let leaf = |population| Node { level: 0, children: [DEAD; 4], population };
+        let mut life = HashLife { nodes: vec![leaf(0), leaf(1)], index: HashMap::new(), results: HashMap::new(),
+            empty: vec![DEAD], root: DEAD, origin: (-4, -4), rule };
+        life.root = life.empty(3);
+        life
+    }
+
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        self.results.clear();
+    }
+
+    pub fn population(&self) -> u64 {
+        self.nodes[self.root as usize].population
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    //Bytes taken by the node store and the memoized results
+    pub fn memory_usage(&self) -> usize {
+        vec_bytes(&self.nodes) + map_bytes(&self.index) + map_bytes(&self.results) + vec_bytes(&self.empty)
+    }
+
+    //Drop every cell but keep the node store, nodes are likely to be reused
+    pub fn clear(&mut self) {
+        self.root = self.empty(3);
+        self.origin = (-4, -4);
+    }
+
+    pub fn get(&self, row: i64, column: i64) -> Cell {
+        let (mut r, mut c) = (row - self.origin.0, column - self.origin.1);
+        let mut node = self.root;
+        let size = 1i64 << self.level(node);
+        if r < 0 || c < 0 || r >= size || c >= size {
+            return Cell::Dead;
+        }
+        while self.level(node) > 0 {
+            let half = 1i64 << (self.level(node) - 1);
+            let quadrant = (r >= half) as usize * 2 + (c >= half) as usize;
+            node = self.nodes[node as usize].children[quadrant];
+            r %= half;
+            c %= half;
+        }
+        if node == ALIVE { Cell::Alive } else { Cell::Dead }
+    }
+
+    pub fn set(&mut self, row: i64, column: i64, state: Cell) {
+        loop {
+            let size = 1i64 << self.level(self.root);
+            let (r, c) = (row - self.origin.0, column - self.origin.1);
+            if r >= 0 && c >= 0 && r < size && c < size {
+                let root = self.root;
+                self.root = self.set_in(root, r, c, state);
+                return;
+            }
+            self.expand();
+        }
+    }
+
+    //Row and column of every live cell
+    pub fn live_cells(&self, out: &mut Vec<(i64, i64)>) {
+        self.collect(self.root, self.origin, out);
+    }
+
+    //Run 2^j generations at once
+    pub fn step(&mut self, j: u8) {
+        loop {
+            let level = self.level(self.root);
+            let inner = self.inner_population();
+            if level >= j + 3 && inner == self.population() {
+                break;
+            }
+            self.expand();
+        }
+        let level = self.level(self.root);
+        let root = self.root;
+        self.root = self.successor(root, j);
+        let shift = 1i64 << (level - 2);
+        self.origin = (self.origin.0 + shift, self.origin.1 + shift);
+    }
+
+    fn level(&self, id: u32) -> u8 {
+        self.nodes[id as usize].level
+    }
+
+    fn children(&self, id: u32) -> [u32; 4] {
+        self.nodes[id as usize].children
+    }
+
+    fn join(&mut self, children: [u32; 4]) -> u32 {
+        if let Some(&id) = self.index.get(&children) {
+            return id;
+        }
+        let level = self.level(children[0]) + 1;
+        let population = children.iter().map(|&child| self.nodes[child as usize].population).sum();
+        let id = self.nodes.len() as u32;
+        self.nodes.push(Node { level, children, population });
+        self.index.insert(children, id);
+        id
+    }
+
+    fn empty(&mut self, level: u8) -> u32 {
+        while self.empty.len() <= level as usize {
+            let below = self.empty[self.empty.len() - 1];
+            let node = self.join([below; 4]);
+            self.empty.push(node);
+        }
+        self.empty[level as usize]
+    }
+
+    //Put an empty border around the root, doubling its size with the old root in the middle
+    fn expand(&mut self) {
+        let level = self.level(self.root);
+        let e = self.empty(level - 1);
+        let [nw, ne, sw, se] = self.children(self.root);
+        let quadrants = [self.join([e, e, e, nw]), self.join([e, e, ne, e]), self.join([e, sw, e, e]), self.join([se, e, e, e])];
+        self.root = self.join(quadrants);
+        let shift = 1i64 << (level - 1);
+        self.origin = (self.origin.0 - shift, self.origin.1 - shift);
+    }
+
+    //Level k - 1 node in the middle of a level k node
+    fn centre(&mut self, id: u32) -> u32 {
+        let [nw, ne, sw, se] = self.children(id);
+        self.join([self.children(nw)[3], self.children(ne)[2], self.children(sw)[1], self.children(se)[0]])
+    }
+
+    //Cells in the middle quarter (half the side) of the root
+    fn inner_population(&mut self) -> u64 {
+        let root = self.root;
+        let centre = self.centre(root);
+        let inner = self.centre(centre);
+        self.nodes[inner as usize].population
+    }
+
+    fn set_in(&mut self, id: u32, row: i64, column: i64, state: Cell) -> u32 {
+        let level = self.level(id);
+        if level == 0 {
+            return if state == Cell::Alive { ALIVE } else { DEAD };
+        }
+        let half = 1i64 << (level - 1);
+        let quadrant = (row >= half) as usize * 2 + (column >= half) as usize;
+        let mut children = self.children(id);
+        children[quadrant] = self.set_in(children[quadrant], row % half, column % half, state);
+        self.join(children)
+    }
+
+    fn collect(&self, id: u32, (row, column): (i64, i64), out: &mut Vec<(i64, i64)>) {
+        let node = self.nodes[id as usize];
+        if node.population == 0 {
+            return;
+        }
+        if node.level == 0 {
+            out.push((row, column));
+            return;
+        }
+        let half = 1i64 << (node.level - 1);
+        for (quadrant, &child) in node.children.iter().enumerate() {
+            let (r, c) = ((quadrant / 2) as i64 * half, (quadrant % 2) as i64 * half);
+            self.collect(child, (row + r, column + c), out);
+        }
+    }
+
+    //Centre of a level k node (k >= 2) advanced by 2^j generations, j <= k - 2
+    fn successor(&mut self, id: u32, j: u8) -> u32 {
+        let level = self.level(id);
+        if self.nodes[id as usize].population == 0 {
+            return self.empty(level - 1);
+        }
+        if let Some(&result) = self.results.get(&(id, j)) {
+            return result;
+        }
+        let result = if level == 2 {
+            self.base(id)
+        } else {
+            let [nw, ne, sw, se] = self.children(id);
+            let (cnw, cne, csw, cse) = (self.children(nw), self.children(ne), self.children(sw), self.children(se));
+            let nine = [
+                nw,
+                self.join([cnw[1], cne[0], cnw[3], cne[2]]),
+                ne,
+                self.join([cnw[2], cnw[3], csw[0], csw[1]]),
+                self.join([cnw[3], cne[2], csw[1], cse[0]]),
+                self.join([cne[2], cne[3], cse[0], cse[1]]),
+                sw,
+                self.join([csw[1], cse[0], csw[3], cse[2]]),
+                se,
+            ];
+            //Either advance in both halves at full speed, or only in the second one
+            let full = j == level - 2;
+            let mut parts = [DEAD; 9];
+            for (part, &node) in parts.iter_mut().zip(nine.iter()) {
+                *part = if full { self.successor(node, j - 1) } else { self.centre(node) };
+            }
+            let next_j = if full { j - 1 } else { j };
+            let mut quadrants = [DEAD; 4];
+            for (quadrant, &(r, c)) in quadrants.iter_mut().zip([(0, 0), (0, 1), (1, 0), (1, 1)].iter()) {
+                let at = |dr: usize, dc: usize| parts[(r + dr) * 3 + c + dc];
+                let node = self.join([at(0, 0), at(0, 1), at(1, 0), at(1, 1)]);
+                *quadrant = self.successor(node, next_j);
+            }
+            self.join(quadrants)
+        };
+        self.results.insert((id, j), result);
+        result
+    }
+
+    //Middle 2x2 of a 4x4 node after one generation
+    fn base(&mut self, id: u32) -> u32 {
+        let mut grid = [[0u8; 4]; 4];
+        for (quadrant, &child) in self.children(id).iter().enumerate() {
+            for (cell, &leaf) in self.children(child).iter().enumerate() {
+                grid[quadrant / 2 * 2 + cell / 2][quadrant % 2 * 2 + cell % 2] = leaf as u8;
+            }
+        }
+        let mut centre = [DEAD; 4];
+        for (cell, &(r, c)) in centre.iter_mut().zip([(1, 1), (1, 2), (2, 1), (2, 2)].iter()) {
+            let config = MOORE_OFFSETS.iter()
+                .fold(0u8, |config, &(dr, dc)| config << 1 | grid[(r + dr) as usize][(c + dc) as usize]);
+            let state = if grid[r as usize][c as usize] == 1 { Cell::Alive } else { Cell::Dead };
+            *cell = if self.rule.next_from_moore(state, config) == Cell::Alive { ALIVE } else { DEAD };
+        }
+        self.join(centre)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_share_identical_nodes() {
+        let mut life = HashLife::new(Rule::conway());
+        life.set(0, 0, Cell::Alive);
+        life.set(100, -100, Cell::Alive);
+        life.set(0, 0, Cell::Dead);
+        let nodes = life.node_count();
+        life.set(0, 0, Cell::Alive);
+        assert_eq!(life.node_count(), nodes);
+        assert_eq!(life.population(), 2);
+        assert_eq!(life.get(100, -100), Cell::Alive);
+    }
+
+    #[test]
+    fn should_jump_glider_ahead() {
+        let mut life = HashLife::new(Rule::conway());
+        for &(r, c) in [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)].iter() {
+            life.set(r, c, Cell::Alive);
+        }
+        life.step(10);
+        let mut cells = Vec::new();
+        life.live_cells(&mut cells);
+        cells.sort();
+        assert_eq!(cells, vec![(256, 257), (257, 258), (258, 256), (258, 257), (258, 258)]);
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use error::Error;
+use universe::Cell;
+use super::HashLife;
+
+//Deepest node a macrocell file may hold, cell coordinates have to fit an i64
+const MAX_LEVEL: u8 = 60;
+
+//Body lines of Golly's macrocell format, the header is left to the caller. Every distinct non-empty
+//node is one line, numbered from 1 in the order written. Level 3 nodes are 8x8 leaves of . and *
+//with $ ending each row, trailing dead cells and rows left out; bigger ones are "level nw ne sw se"
+//with the line numbers of their quadrants, 0 for empty ones. The last line is the root, with its
+//centre at row 0, column 0
+impl HashLife {
+    pub fn write_macrocell(&self, out: &mut String) {
+        let level = self.level(self.root);
+        if self.population() == 0 {
+            return;
+        }
+        if level < 3 {
+            //Centred inside a single leaf
+            let offset = 4 - (1i64 << level) / 2;
+            out.push_str(&self.leaf_line(self.root, offset));
+            out.push('\n');
+            return;
+        }
+        self.write_node(self.root, &mut HashMap::new(), out);
+    }
+
+    //Replace the cells with the tree in the lines, each with its line number in the file for errors
+    pub fn read_macrocell<'a, I: IntoIterator<Item = (usize, &'a str)>>(&mut self, lines: I) -> Result<(), Error> {
+        let mut ids: Vec<u32> = Vec::new();
+        for (number, line) in lines {
+            let id = if line.starts_with(['.', '*', '$']) { self.read_leaf(line) } else { self.read_node(line, &ids) };
+            ids.push(id.map_err(|message| Error::Parse { format: "macrocell", line: number, message })?);
+        }
+        match ids.last() {
+            Some(&root) => {
+                let half = 1i64 << (self.level(root) - 1);
+                self.root = root;
+                self.origin = (-half, -half);
+            }
+            None => self.clear(),
+        }
+        Ok(())
+    }
+
+    //Line number of the node, writing it and its quadrants first when they are new
+    fn write_node(&self, id: u32, lines: &mut HashMap<u32, usize>, out: &mut String) -> usize {
+        let node = self.nodes[id as usize];
+        if node.population == 0 {
+            return 0;
+        }
+        if let Some(&line) = lines.get(&id) {
+            return line;
+        }
+        if node.level == 3 {
+            out.push_str(&self.leaf_line(id, 0));
+        } else {
+            let quadrants: Vec<String> = node.children.iter().map(|&child| self.write_node(child, lines, out).to_string()).collect();
+            out.push_str(&format!("{} {}", node.level, quadrants.join(" ")));
+        }
+        out.push('\n');
+        let line = lines.len() + 1;
+        lines.insert(id, line);
+        line
+    }
+
+    //Rows of a node of level 3 or less placed offset rows and columns into a leaf
+    fn leaf_line(&self, id: u32, offset: i64) -> String {
+        let mut cells = Vec::new();
+        self.collect(id, (offset, offset), &mut cells);
+        let mut rows = [0u8; 8];
+        for (row, col) in cells {
+            rows[row as usize] |= 1 << col;
+        }
+        let last = rows.iter().rposition(|&row| row != 0).unwrap_or(0);
+        rows[..=last].iter()
+            .map(|&row| (0..8 - row.leading_zeros()).map(|col| if row >> col & 1 == 1 { '*' } else { '.' }).collect::<String>() + "$")
+            .collect()
+    }
+
+    fn read_leaf(&mut self, line: &str) -> Result<u32, String> {
+        let (mut id, mut row, mut col) = (self.empty(3), 0, 0);
+        for c in line.chars() {
+            match c {
+                '$' => {
+                    row += 1;
+                    col = 0;
+                    continue;
+                }
+                '*' if row < 8 && col < 8 => id = self.set_in(id, row, col, Cell::Alive),
+                '.' => {}
+                '*' => return Err(format!("leaf {} is bigger than 8x8", line)),
+                _ => return Err(format!("unexpected {} in leaf {}", c, line)),
+            }
+            col += 1;
+        }
+        Ok(id)
+    }
+
+    fn read_node(&mut self, line: &str, ids: &[u32]) -> Result<u32, String> {
+        let numbers: Vec<usize> = line.split_whitespace().map(str::parse).collect::<Result<_, _>>()
+            .map_err(|_| format!("invalid node {}", line))?;
+        let (level, quadrants) = match numbers.split_first() {
+            Some((&level, quadrants)) if quadrants.len() == 4 => (level, quadrants),
+            _ => return Err(format!("invalid node {}", line)),
+        };
+        if level < 4 || level > MAX_LEVEL as usize {
+            return Err(format!("unsupported node level {}, only two state patterns can be read", level));
+        }
+        let below = self.empty(level as u8 - 1);
+        let mut children = [below; 4];
+        for (child, &number) in children.iter_mut().zip(quadrants) {
+            if number == 0 {
+                continue;
+            }
+            *child = *ids.get(number - 1).ok_or_else(|| format!("node {} refers to line {} before it", line, number))?;
+            if self.level(*child) as usize != level - 1 {
+                return Err(format!("node {} has a quadrant of the wrong size", line));
+            }
+        }
+        Ok(self.join(children))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rule::Rule;
+
+    fn cells(life: &HashLife) -> Vec<(i64, i64)> {
+        let mut cells = Vec::new();
+        life.live_cells(&mut cells);
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn should_read_and_write_macrocell_nodes() {
+        let mut life = HashLife::new(Rule::conway());
+        life.read_macrocell(vec![(1, "$$..*$...*$.***$"), (2, "4 0 0 0 1")]).unwrap();
+        assert_eq!(cells(&life), vec![(2, 2), (3, 3), (4, 1), (4, 2), (4, 3)]);
+
+        //Two far apart copies share their leaf line
+        life.set(-1000, -1000, Cell::Alive);
+        life.set(-1000, -999, Cell::Alive);
+        let mut text = String::new();
+        life.write_macrocell(&mut text);
+        assert_eq!(text.lines().filter(|line| line.ends_with('$')).count(), 2);
+        let mut copy = HashLife::new(Rule::conway());
+        copy.read_macrocell(text.lines().enumerate()).unwrap();
+        assert_eq!(cells(&copy), cells(&life));
+
+        assert!(copy.read_macrocell(vec![(1, "4 0 0 0 1")]).is_err());
+        assert_eq!(copy.read_macrocell(vec![(1, "$*$"), (2, "1 0 0 0 1")]).unwrap_err().to_string(),
+            "bad macrocell at line 2: unsupported node level 1, only two state patterns can be read");
+    }
+}
@@ -0,0 +1,111 @@
+//Isotropic non-totalistic neighbourhoods in Hensel notation. A configuration is the byte of the
+//eight Moore neighbours read row by row: bit 7 is the north-west cell, bit 0 the south-east one.
+//Each letter of a count stands for one configuration up to rotation and reflection
+
+use rule::MOORE_OFFSETS;
+
+//Set of neighbourhood configurations, one bit per possible byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ConfigSet([u128; 2]);
+
+impl ConfigSet {
+    pub fn contains(&self, config: u8) -> bool {
+        self.0[(config >> 7) as usize] & 1 << (config & 0x7f) != 0
+    }
+
+    pub fn insert(&mut self, config: u8) {
+        self.0[(config >> 7) as usize] |= 1 << (config & 0x7f);
+    }
+
+    pub fn remove(&mut self, config: u8) {
+        self.0[(config >> 7) as usize] &= !(1 << (config & 0x7f));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == [0, 0]
+    }
+
+    //Every configuration with that many live neighbours
+    pub fn with_count(count: u32) -> ConfigSet {
+        let mut set = ConfigSet::default();
+        for config in (0..=255u8).filter(|config| config.count_ones() == count) {
+            set.insert(config);
+        }
+        set
+    }
+}
+
+//Letters of each count in canonical order
+pub fn letters(count: u32) -> &'static str {
+    match count {
+        1 | 7 => "ce",
+        2 | 6 => "cekain",
+        3 | 5 => "cekainyqjr",
+        4 => "cekainyqjrtwz",
+        _ => "c",
+    }
+}
+
+//One configuration for each letter of the counts up to 4, the others are their complements
+const REPRESENTATIVES: [(u32, char, u8); 32] = [
+    (0, 'c', 0x00),
+    (1, 'c', 0x01), (1, 'e', 0x02),
+    (2, 'c', 0x05), (2, 'e', 0x0a), (2, 'k', 0x0c), (2, 'a', 0x03), (2, 'i', 0x18), (2, 'n', 0x24),
+    (3, 'c', 0x25), (3, 'e', 0x1a), (3, 'k', 0x32), (3, 'a', 0x0b), (3, 'i', 0x07),
+    (3, 'n', 0x0d), (3, 'y', 0x31), (3, 'q', 0x26), (3, 'j', 0x0e), (3, 'r', 0x19),
+    (4, 'c', 0xa5), (4, 'e', 0x5a), (4, 'k', 0x33), (4, 'a', 0x0f), (4, 'i', 0x1d), (4, 'n', 0x27),
+    (4, 'y', 0x35), (4, 'q', 0x36), (4, 'j', 0x3a), (4, 'r', 0x1b), (4, 't', 0x39), (4, 'w', 0x2e),
+    (4, 'z', 0x3c),
+];
+
+//Move the neighbours of a configuration around, bit 7 - n is the n-th Moore offset
+fn map_offsets<F: Fn((i32, i32)) -> (i32, i32)>(config: u8, f: F) -> u8 {
+    MOORE_OFFSETS.iter().enumerate()
+        .filter(|&(bit, _)| config & 0x80 >> bit != 0)
+        .fold(0, |mapped, (_, &offset)| {
+            let target = MOORE_OFFSETS.iter().position(|&other| other == f(offset)).unwrap_or(0);
+            mapped | 0x80 >> target
+        })
+}
+
+//Configurations of one letter of a count, None when the count has no such letter
+pub fn configs(count: u32, letter: char) -> Option<ConfigSet> {
+    let (base, complement) = if count > 4 { (8 - count, true) } else { (count, false) };
+    let &(_, _, representative) = REPRESENTATIVES.iter()
+        .find(|&&(n, l, _)| n == base && l == letter && count <= 8)?;
+    let mut set = ConfigSet::default();
+    for reflected in [representative, map_offsets(representative, |(row, col)| (row, -col))].iter() {
+        let mut config = *reflected;
+        for _ in 0..4 {
+            set.insert(if complement { !config } else { config });
+            config = map_offsets(config, |(row, col)| (col, -row));
+        }
+    }
+    Some(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_partition_configurations_by_letter() {
+        for count in 0..=8 {
+            let mut union = ConfigSet::default();
+            let mut size = 0;
+            for letter in letters(count).chars() {
+                let set = configs(count, letter).unwrap();
+                for config in (0..=255u8).filter(|&config| set.contains(config)) {
+                    assert!(!union.contains(config));
+                    assert_eq!(config.count_ones(), count);
+                    union.insert(config);
+                    size += 1;
+                }
+            }
+            assert_eq!(union, ConfigSet::with_count(count));
+            assert!(size > 0);
+        }
+        assert!(configs(1, 'a').is_none());
+        assert!(configs(2, 'n').unwrap().contains(0x81));
+    }
+}
@@ -0,0 +1,367 @@
+use wasm_bindgen::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use error::Error;
+use rule::{Rule, MOORE_OFFSETS};
+use universe::Cell;
+use hashlife::HashLife;
+use utils::{map_bytes, vec_bytes};
+
+//Side of the square chunks the plane is cut into
+pub const CHUNK_SIZE: i32 = 64;
+
+type Chunk = Vec<Cell>;
+
+//How an InfiniteUniverse stores and runs its cells
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    //Chunks ticked one generation at a time, the cost follows the live area
+    Chunked,
+    //Memoized quadtree, tick_n jumps ahead in powers of two. Much faster on large regular
+    //patterns (guns, breeders), slower on chaotic ones
+    HashLife,
+}
+
+//Unbounded Life plane. With the chunked engine CHUNK_SIZE x CHUNK_SIZE chunks are kept in a map
+//by chunk coordinates, allocated when a pattern grows into them and dropped once they are empty
+//again. With HashLife the cells live in the quadtree instead and chunks stays empty
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct InfiniteUniverse {
+    chunks: HashMap<(i32, i32), Chunk>,
+    tree: Option<HashLife>,
+    rule: Rule,
+    generation: u32,
+    population: u32,
+}
+
+//Chunk holding a cell and the cell's offset inside it
+fn split(row: i32, column: i32) -> ((i32, i32), usize) {
+    let key = (row.div_euclid(CHUNK_SIZE), column.div_euclid(CHUNK_SIZE));
+    let offset = row.rem_euclid(CHUNK_SIZE) * CHUNK_SIZE + column.rem_euclid(CHUNK_SIZE);
+    (key, offset as usize)
+}
+
+#[wasm_bindgen]
+impl InfiniteUniverse {
+    //Empty plane running Conway's Life on the chunked engine
+    pub fn new() -> InfiniteUniverse {
+        InfiniteUniverse::with_engine(Engine::Chunked)
+    }
+
+    pub fn with_engine(engine: Engine) -> InfiniteUniverse {
+        let tree = if engine == Engine::HashLife { Some(HashLife::new(Rule::conway())) } else { None };
+        InfiniteUniverse { chunks: HashMap::new(), tree, rule: Rule::conway(), generation: 0, population: 0 }
+    }
+
+    pub fn engine(&self) -> Engine {
+        if self.tree.is_some() { Engine::HashLife } else { Engine::Chunked }
+    }
+
+    pub fn tick(&mut self) {
+        if let Some(ref mut tree) = self.tree {
+            tree.step(0);
+            self.population = tree.population() as u32;
+            self.generation += 1;
+            return;
+        }
+        let mut candidates = HashSet::new();
+        for &(row, col) in self.chunks.keys() {
+            for (r, c) in iproduct!(-1..=1, -1..=1) {
+                candidates.insert((row + r, col + c));
+            }
+        }
+        let mut next = HashMap::new();
+        let mut population = 0;
+        for key in candidates {
+            let chunk = self.next_chunk(key);
+            let alive = chunk.iter().filter(|&&cell| cell == Cell::Alive).count() as u32;
+            if alive > 0 {
+                population += alive;
+                next.insert(key, chunk);
+            }
+        }
+        self.chunks = next;
+        self.population = population;
+        self.generation += 1;
+    }
+
+    //HashLife runs n as a sum of powers of two, one super-step each
+    pub fn tick_n(&mut self, n: u32) {
+        if let Some(ref mut tree) = self.tree {
+            for j in (0..32).filter(|j| n & 1 << j != 0) {
+                tree.step(j as u8);
+            }
+            self.population = tree.population() as u32;
+            self.generation += n;
+            return;
+        }
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+
+    //Switch to another Life-like rule given in B/S notation
+    #[wasm_bindgen(js_name = set_rule)]
+    pub fn set_rule_js(&mut self, rule: &str) -> Result<(), Error> {
+        let rule = rule.parse::<Rule>()?;
+        self.set_rule(rule);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = rule)]
+    pub fn rule_string(&self) -> String {
+        self.rule.to_string()
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    pub fn population(&self) -> u32 {
+        self.population
+    }
+
+    //Number of allocated chunks
+    pub fn chunk_count(&self) -> u32 {
+        self.chunks.len() as u32
+    }
+
+    //Quadtree nodes held by the HashLife engine, they are never freed
+    pub fn node_count(&self) -> u32 {
+        self.tree.as_ref().map_or(0, |tree| tree.node_count() as u32)
+    }
+
+    //Bytes taken by the chunks or the HashLife quadtree
+    pub fn memory_usage(&self) -> usize {
+        mem::size_of::<InfiniteUniverse>() + map_bytes(&self.chunks)
+            + self.chunks.values().map(vec_bytes).sum::<usize>()
+            + self.tree.as_ref().map_or(0, HashLife::memory_usage)
+    }
+
+    pub fn get_cell(&self, row: i32, column: i32) -> Cell {
+        if let Some(ref tree) = self.tree {
+            return tree.get(i64::from(row), i64::from(column));
+        }
+        let (key, offset) = split(row, column);
+        self.chunks.get(&key).map_or(Cell::Dead, |chunk| chunk[offset])
+    }
+
+    pub fn set_cell(&mut self, row: i32, column: i32, state: Cell) {
+        if let Some(ref mut tree) = self.tree {
+            tree.set(i64::from(row), i64::from(column), state);
+            self.population = tree.population() as u32;
+            return;
+        }
+        let (key, offset) = split(row, column);
+        if state == Cell::Dead && !self.chunks.contains_key(&key) {
+            return;
+        }
+        let chunk = self.chunks.entry(key).or_insert_with(|| vec![Cell::Dead; (CHUNK_SIZE * CHUNK_SIZE) as usize]);
+        self.population = self.population + state as u32 - chunk[offset] as u32;
+        chunk[offset] = state;
+        if chunk.iter().all(|&cell| cell == Cell::Dead) {
+            self.chunks.remove(&key);
+        }
+    }
+
+    //One byte per cell (1 alive) of the height x width rectangle with its top left corner at
+    //row, column, row by row. Meant for drawing whatever part of the plane the camera shows
+    pub fn cells_in_rect(&self, row: i32, column: i32, height: u32, width: u32) -> Vec<u8> {
+        iproduct!(0..height as i32, 0..width as i32)
+            .map(|(r, c)| self.get_cell(row + r, column + c) as u8)
+            .collect()
+    }
+
+    //Packed [row0, col0, row1, col1, ...] of every live cell, chunk by chunk
+    pub fn live_cells(&self) -> Vec<i32> {
+        let mut coords = Vec::with_capacity(self.population as usize * 2);
+        if let Some(ref tree) = self.tree {
+            let mut cells = Vec::new();
+            tree.live_cells(&mut cells);
+            coords.extend(cells.iter().flat_map(|&(row, col)| vec![row as i32, col as i32]));
+            return coords;
+        }
+        for (&(chunk_row, chunk_col), chunk) in &self.chunks {
+            for (offset, _) in chunk.iter().enumerate().filter(|(_, &cell)| cell == Cell::Alive) {
+                coords.push(chunk_row * CHUNK_SIZE + offset as i32 / CHUNK_SIZE);
+                coords.push(chunk_col * CHUNK_SIZE + offset as i32 % CHUNK_SIZE);
+            }
+        }
+        coords
+    }
+
+    pub fn clear(&mut self) {
+        if let Some(ref mut tree) = self.tree {
+            tree.clear();
+        }
+        self.chunks.clear();
+        self.population = 0;
+        self.generation = 0;
+    }
+
+    //Plane on the HashLife engine from Golly's macrocell (.mc) format. The quadtree is read node by
+    //node, so huge repetitive patterns (metapixels) never get expanded into cells
+    pub fn from_macrocell(text: &str) -> Result<InfiniteUniverse, Error> {
+        InfiniteUniverse::parse_macrocell(text)
+    }
+
+    //Macrocell text with the rule and generation, the chunked engine's cells are put into a
+    //quadtree first
+    pub fn to_macrocell(&self) -> String {
+        let mut text = format!("[M2] (wasm-game-of-life)\n#R {}\n#G {}\n", self.rule, self.generation);
+        match self.tree {
+            Some(ref tree) => tree.write_macrocell(&mut text),
+            None => {
+                let mut tree = HashLife::new(self.rule);
+                for cell in self.live_cells().chunks(2) {
+                    tree.set(i64::from(cell[0]), i64::from(cell[1]), Cell::Alive);
+                }
+                tree.write_macrocell(&mut text);
+            }
+        }
+        text
+    }
+}
+
+impl InfiniteUniverse {
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        if let Some(ref mut tree) = self.tree {
+            tree.set_rule(rule);
+        }
+    }
+
+    //#R sets the rule (Conway's without one) and #G the generation, other # lines are skipped
+    pub fn parse_macrocell(text: &str) -> Result<InfiniteUniverse, Error> {
+        let error = |line, message| Error::Parse { format: "macrocell", line, message };
+        let mut univ = InfiniteUniverse::with_engine(Engine::HashLife);
+        let mut nodes = Vec::new();
+        let lines = text.lines().map(str::trim).enumerate().map(|(number, line)| (number + 1, line));
+        for (number, line) in lines.filter(|(_, line)| !line.is_empty()) {
+            if let Some(rule) = line.strip_prefix("#R") {
+                univ.set_rule(rule.trim().parse().map_err(|e: Error| error(number, e.to_string()))?);
+            } else if let Some(generation) = line.strip_prefix("#G") {
+                univ.generation = generation.trim().parse().map_err(|_| error(number, format!("invalid generation {}", generation)))?;
+            } else if !line.starts_with(['#', '[']) {
+                nodes.push((number, line));
+            }
+        }
+        if let Some(ref mut tree) = univ.tree {
+            tree.read_macrocell(nodes)?;
+            univ.population = tree.population() as u32;
+        }
+        Ok(univ)
+    }
+
+    //Next generation of one chunk, looking into the eight chunks around it for the border cells
+    fn next_chunk(&self, (chunk_row, chunk_col): (i32, i32)) -> Chunk {
+        let around: Vec<Option<&Chunk>> = iproduct!(-1..=1, -1..=1)
+            .map(|(r, c)| self.chunks.get(&(chunk_row + r, chunk_col + c)))
+            .collect();
+        let at = |row: i32, col: i32| -> u8 {
+            let pick = (row.div_euclid(CHUNK_SIZE) + 1) * 3 + col.div_euclid(CHUNK_SIZE) + 1;
+            let offset = row.rem_euclid(CHUNK_SIZE) * CHUNK_SIZE + col.rem_euclid(CHUNK_SIZE);
+            around[pick as usize].map_or(0, |chunk| chunk[offset as usize] as u8)
+        };
+        iproduct!(0..CHUNK_SIZE, 0..CHUNK_SIZE)
+            .map(|(row, col)| {
+                let config = MOORE_OFFSETS.iter().fold(0u8, |config, &(r, c)| config << 1 | at(row + r, col + c));
+                let cell = if at(row, col) == 1 { Cell::Alive } else { Cell::Dead };
+                self.rule.next_from_moore(cell, config)
+            })
+            .collect()
+    }
+}
+
+impl Default for InfiniteUniverse {
+    fn default() -> InfiniteUniverse {
+        InfiniteUniverse::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use universe::Cell::*;
+
+    fn glider(univ: &mut InfiniteUniverse, row: i32, column: i32) {
+        for &(r, c) in [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)].iter() {
+            univ.set_cell(row + r, column + c, Alive);
+        }
+    }
+
+    #[test]
+    fn should_match_chunked_engine_with_hashlife() {
+        let mut chunked = InfiniteUniverse::new();
+        let mut tree = InfiniteUniverse::with_engine(Engine::HashLife);
+        for univ in [&mut chunked, &mut tree].iter_mut() {
+            glider(univ, 0, 0);
+            glider(univ, 10, -20);
+            univ.set_cell(-5, 5, Alive);
+            univ.set_cell(-5, 6, Alive);
+            univ.set_cell(-4, 5, Alive);
+            univ.tick_n(37);
+        }
+        assert_eq!(tree.engine(), Engine::HashLife);
+        assert_eq!(tree.generation(), 37);
+        assert_eq!(tree.population(), chunked.population());
+        assert_eq!(tree.cells_in_rect(-30, -40, 80, 80), chunked.cells_in_rect(-30, -40, 80, 80));
+        assert_eq!(tree.chunk_count(), 0);
+    }
+
+    #[test]
+    fn should_round_trip_macrocell() {
+        let mut chunked = InfiniteUniverse::new();
+        chunked.set_rule("B36/S23".parse().unwrap());
+        glider(&mut chunked, -40, 30);
+        chunked.tick_n(3);
+        let text = chunked.to_macrocell();
+        assert!(text.starts_with("[M2] (wasm-game-of-life)\n#R B36/S23\n#G 3\n"));
+
+        let tree = InfiniteUniverse::parse_macrocell(&text).unwrap();
+        assert_eq!((tree.engine(), tree.rule(), tree.generation()), (Engine::HashLife, chunked.rule(), 3));
+        assert_eq!(tree.population(), 5);
+        assert_eq!(tree.cells_in_rect(-50, 20, 20, 20), chunked.cells_in_rect(-50, 20, 20, 20));
+        assert_eq!(tree.to_macrocell(), text);
+        assert!(InfiniteUniverse::parse_macrocell("#G x").is_err());
+    }
+
+    #[test]
+    fn should_allocate_chunks_lazily() {
+        let mut univ = InfiniteUniverse::new();
+        univ.set_cell(-1, -1, Alive);
+        univ.set_cell(500, 0, Dead);
+        assert_eq!(univ.chunk_count(), 1);
+        assert_eq!(univ.get_cell(-1, -1), Alive);
+        assert_eq!(univ.population(), 1);
+        let one_chunk = univ.memory_usage();
+        univ.set_cell(1000, 1000, Alive);
+        assert!(univ.memory_usage() >= one_chunk + (CHUNK_SIZE * CHUNK_SIZE) as usize);
+        univ.set_cell(1000, 1000, Dead);
+        univ.set_cell(-1, -1, Dead);
+        assert_eq!(univ.chunk_count(), 0);
+    }
+
+    #[test]
+    fn should_fly_glider_across_chunks() {
+        let mut univ = InfiniteUniverse::new();
+        glider(&mut univ, 60, 60);
+        univ.tick_n(40);
+        assert_eq!(univ.population(), 5);
+        assert_eq!(univ.cells_in_rect(70, 70, 3, 3), vec![0, 1, 0, 0, 0, 1, 1, 1, 1]);
+        assert!(univ.chunks.keys().all(|&key| key == (1, 1)));
+
+        let mut backwards = InfiniteUniverse::new();
+        backwards.set_rule("B3/S23".parse().unwrap());
+        glider(&mut backwards, -3, -3);
+        backwards.tick_n(4);
+        assert_eq!(backwards.get_cell(-1, 0), Alive);
+        assert_eq!(backwards.live_cells().len(), 10);
+    }
+}
@@ -1,15 +1,79 @@
 extern crate cfg_if;
 extern crate wasm_bindgen;
 extern crate js_sys;
+extern crate web_sys;
+extern crate base64;
+extern crate serde;
+extern crate serde_json;
+extern crate serde_wasm_bindgen;
+#[cfg(feature = "threads")]
+extern crate rayon;
+#[cfg(feature = "threads")]
+extern crate wasm_bindgen_rayon;
+#[cfg(feature = "png-export")]
+extern crate png;
+#[cfg(feature = "png-export")]
+extern crate crc32fast;
+#[cfg(feature = "gif-export")]
+extern crate gif;
+#[cfg(feature = "compress")]
+extern crate flate2;
+#[cfg(any(feature = "fetch", feature = "indexed-db"))]
+extern crate futures;
+#[cfg(any(feature = "fetch", feature = "indexed-db"))]
+extern crate wasm_bindgen_futures;
 #[macro_use] extern crate itertools;
 
+#[allow(unused_macros)]
 macro_rules! log {
     ( $( $t:tt)* ) => {
+        #[cfg(target_arch = "wasm32")]
         web_sys::console::log_1(&format!( $( $t )* ).into());
     }
 }
 
+//Time the rest of the enclosing block with a Timer, only compiled in with the profiling feature
+macro_rules! profile {
+    ( $name:expr ) => {
+        #[cfg(feature = "profiling")]
+        let _timer = ::utils::Timer::new($name);
+    }
+}
+
+//Index a slice in the hot tick loops, without the bounds check in release builds with the
+//fast-unsafe feature. Callers check their bounds up front, debug builds still check every access
+macro_rules! at {
+    ( $slice:expr, $idx:expr ) => {{
+        #[cfg(all(feature = "fast-unsafe", not(debug_assertions)))]
+        let value = unsafe { *$slice.get_unchecked($idx) };
+        #[cfg(not(all(feature = "fast-unsafe", not(debug_assertions))))]
+        let value = $slice[$idx];
+        value
+    }}
+}
+
+//Per cell tracing, only compiled in with the debug-trace feature and then logged while on is true
+macro_rules! trace {
+    ( $on:expr, $( $t:tt)* ) => {
+        #[cfg(feature = "debug-trace")]
+        {
+            if $on {
+                log!( $( $t )* );
+            }
+        }
+    }
+}
+
 mod utils;
+mod rng;
+mod hensel;
+mod hashlife;
+
+//Start the Web Worker pool ticks run on, JS has to await it once before ticking
+#[cfg(feature = "threads")]
+pub use wasm_bindgen_rayon::init_thread_pool;
+
+pub use utils::Timer;
 
 use cfg_if::cfg_if;
 use wasm_bindgen::prelude::*;
@@ -24,7 +88,22 @@ cfg_if! {
     }
 }
 
+pub mod error;
 pub mod universe;
+pub mod pattern;
+pub mod patterns;
+pub mod rule;
+pub mod mode;
+pub mod ant;
+pub mod continuous;
+pub mod infinite;
+pub mod worker;
+pub mod renderer;
+pub mod formats;
+pub mod compress;
+pub mod storage;
+#[cfg(feature = "png-export")]
+pub mod recorder;
 
 #[wasm_bindgen]
 extern {
@@ -33,5 +112,5 @@ extern {
 
 #[wasm_bindgen]
 pub fn get_universe() -> universe::Universe {
-    universe::Universe::new()
+    universe::Universe::default()
 }
\ No newline at end of file
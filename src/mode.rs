@@ -0,0 +1,147 @@
+use wasm_bindgen::prelude::*;
+use rule::{CyclicRule, Rule};
+
+//Which automaton a universe simulates. Life covers every Life-like and Generations rule,
+//the other modes bring their own fixed set of cell states
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Life,
+    Wireworld,
+    //Wolfram's one dimensional automata, each tick appends a row computed from the bottom one
+    //and scrolls the board up
+    Elementary,
+    //Two state rules over a large square neighbourhood, see LtlRule
+    LargerThanLife,
+    //Life where live cells carry one of two colours and newborns take their parents' majority
+    Immigration,
+    //The same with four colours, see colored_next
+    QuadLife,
+    //Rock-paper-scissors like cycle of states, see CyclicRule
+    Cyclic,
+    //Block automaton on the Margolus partition, see BlockRule
+    Margolus,
+}
+
+//Wireworld cell states
+pub const WIRE_EMPTY: u8 = 0;
+pub const WIRE_CONDUCTOR: u8 = 1;
+pub const WIRE_HEAD: u8 = 2;
+pub const WIRE_TAIL: u8 = 3;
+
+//Most colours a coloured Life mode uses, live cells are states 1 to colors()
+pub const MAX_COLORS: usize = 4;
+
+impl Mode {
+    pub fn state_count(self, rule: &Rule, cyclic: &CyclicRule) -> u8 {
+        match self {
+            Mode::Cyclic => cyclic.states(),
+            Mode::Life => rule.states(),
+            Mode::Wireworld => 4,
+            Mode::Elementary | Mode::LargerThanLife | Mode::Margolus => 2,
+            Mode::Immigration | Mode::QuadLife => self.colors() + 1,
+        }
+    }
+
+    //Number of colours live cells can have, 0 for modes that aren't a coloured Life
+    pub fn colors(self) -> u8 {
+        match self {
+            Mode::Immigration => 2,
+            Mode::QuadLife => 4,
+            _ => 0,
+        }
+    }
+
+    //Whether cells of this mode need the per cell state buffer
+    pub fn has_states(self, rule: &Rule, cyclic: &CyclicRule) -> bool {
+        self.state_count(rule, cyclic) > 2
+    }
+
+    //Which states show up as Cell::Alive (and count towards the population)
+    pub fn is_alive(self, state: u8) -> bool {
+        match self {
+            Mode::Life | Mode::Elementary | Mode::LargerThanLife | Mode::Margolus => state == 1,
+            Mode::Wireworld => state != WIRE_EMPTY,
+            Mode::Immigration | Mode::QuadLife | Mode::Cyclic => state != 0,
+        }
+    }
+
+    pub fn symbol(self, state: u8) -> char {
+        match (self, state) {
+            (Mode::Cyclic, _) => ::std::char::from_digit(u32::from(state) % 36, 36).unwrap_or('?'),
+            (_, 0) => '◻',
+            (Mode::Wireworld, WIRE_HEAD) => '●',
+            (Mode::Wireworld, WIRE_TAIL) => '○',
+            (Mode::QuadLife, 3) => '▤',
+            (Mode::QuadLife, 4) => '▥',
+            (_, 1) => '◼',
+            (_, _) => '▣',
+        }
+    }
+}
+
+//Suggested 0xRRGGBB colour of a state out of count: white for dead and black for alive cells,
+//the other states (every state of a cyclic automaton) spread around the colour wheel
+pub fn state_color(mode: Mode, state: u8, count: u8) -> u32 {
+    match (mode, state) {
+        (Mode::Cyclic, _) => hue_color(f64::from(state) / f64::from(count)),
+        (_, 0) => 0xFF_FFFF,
+        (_, 1) => 0x00_0000,
+        (_, _) => hue_color(f64::from(state - 2) / f64::from(count.max(3) - 2)),
+    }
+}
+
+//Fully saturated colour at the given fraction of the way around the hue circle
+fn hue_color(turn: f64) -> u32 {
+    let h = turn.fract() * 6.0;
+    let x = ((1.0 - (h % 2.0 - 1.0).abs()) * 255.0).round() as u32;
+    let (r, g, b) = match h as u32 {
+        0 => (255, x, 0),
+        1 => (x, 255, 0),
+        2 => (0, 255, x),
+        3 => (0, x, 255),
+        4 => (x, 0, 255),
+        _ => (255, 0, x),
+    };
+    r << 16 | g << 8 | b
+}
+
+//Cell below (left, center, right) for elementary rule number rule
+pub fn elementary_next(rule: u8, left: bool, center: bool, right: bool) -> bool {
+    let pattern = (left as u8) << 2 | (center as u8) << 1 | right as u8;
+    rule & (1 << pattern) != 0
+}
+
+//Coloured Life: births and deaths follow the rule on the total of colors (neighbours of each
+//colour), survivors keep their colour and newborns take the most common one among their parents.
+//When no colour wins, as with three parents of different colours in QuadLife, a newborn takes the
+//one colour none of them has
+pub fn colored_next(rule: &Rule, state: u8, colors: &[usize]) -> u8 {
+    let neighbours = colors.iter().sum();
+    match state {
+        0 if rule.is_birth(neighbours) => majority_color(colors),
+        0 => 0,
+        alive if rule.is_survival(neighbours) => alive,
+        _ => 0,
+    }
+}
+
+//Colour with the most parents, on a tie the only missing colour if there is one, else the lowest
+fn majority_color(colors: &[usize]) -> u8 {
+    let most = colors.iter().cloned().max().unwrap_or(0);
+    let winners: Vec<usize> = (0..colors.len()).filter(|&color| colors[color] == most).collect();
+    let missing: Vec<usize> = (0..colors.len()).filter(|&color| colors[color] == 0).collect();
+    let color = if winners.len() > 1 && missing.len() == 1 { missing[0] } else { winners[0] };
+    color as u8 + 1
+}
+
+//An electron head becomes a tail, a tail becomes conductor again and conductor turns into
+//a head next to one or two heads
+pub fn wireworld_next(state: u8, heads: usize) -> u8 {
+    match state {
+        WIRE_HEAD => WIRE_TAIL,
+        WIRE_TAIL => WIRE_CONDUCTOR,
+        WIRE_CONDUCTOR if heads == 1 || heads == 2 => WIRE_HEAD,
+        other => other,
+    }
+}
@@ -0,0 +1,118 @@
+use wasm_bindgen::prelude::*;
+use error::Error;
+use universe::{Cell, MAX_CELLS};
+
+//The eight symmetries of a square, applied to a pattern before it is stamped
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipHorizontal,
+    FlipVertical,
+    FlipDiagonal,
+    FlipAntiDiagonal,
+}
+
+impl Transform {
+    //Dimensions (height, width) of a height x width box after the transform
+    pub fn dimensions(self, height: u32, width: u32) -> (u32, u32) {
+        match self {
+            Transform::Rotate90 | Transform::Rotate270 |
+            Transform::FlipDiagonal | Transform::FlipAntiDiagonal => (width, height),
+            _ => (height, width),
+        }
+    }
+
+    //Where (row, col) of a height x width box ends up after the transform
+    pub fn apply(self, row: u32, col: u32, height: u32, width: u32) -> (u32, u32) {
+        match self {
+            Transform::Identity => (row, col),
+            Transform::Rotate90 => (col, height - 1 - row),
+            Transform::Rotate180 => (height - 1 - row, width - 1 - col),
+            Transform::Rotate270 => (width - 1 - col, row),
+            Transform::FlipHorizontal => (row, width - 1 - col),
+            Transform::FlipVertical => (height - 1 - row, col),
+            Transform::FlipDiagonal => (col, row),
+            Transform::FlipAntiDiagonal => (width - 1 - col, height - 1 - row),
+        }
+    }
+}
+
+//A small standalone block of cells (glider, gun...) to be placed into a universe
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    width: u32,
+    height: u32,
+    cells: Vec<Cell>,
+}
+
+#[wasm_bindgen]
+impl Pattern {
+    //Build from packed [row0, col0, row1, col1, ...] alive coordinates, sized to fit them. Throws
+    //when that size is over MAX_CELLS cells, like a universe would
+    pub fn new(live_cells: &[u32]) -> Result<Pattern, Error> {
+        let coords: Vec<(u32, u32)> = live_cells.chunks(2)
+            .filter(|pair| pair.len() == 2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect();
+        Pattern::from_coords(&coords)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+impl Pattern {
+    pub fn from_coords(live_cells: &[(u32, u32)]) -> Result<Pattern, Error> {
+        let height = live_cells.iter().map(|&(r, _)| u64::from(r) + 1).max().unwrap_or(0);
+        let width = live_cells.iter().map(|&(_, c)| u64::from(c) + 1).max().unwrap_or(0);
+        if width * height > u64::from(MAX_CELLS) {
+            return Err(Error::Invalid(format!("a {}x{} pattern has too many cells", width, height)));
+        }
+        let (width, height) = (width as u32, height as u32);
+        let mut cells = vec![Cell::Dead; (width * height) as usize];
+        for &(r, c) in live_cells {
+            cells[(r * width + c) as usize] = Cell::Alive;
+        }
+        Ok(Pattern { width, height, cells })
+    }
+
+    pub fn get(&self, row: u32, col: u32) -> Cell {
+        self.cells[(row * self.width + col) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_size_pattern_from_coords() {
+        let glider = Pattern::new(&[0, 1, 1, 2, 2, 0, 2, 1, 2, 2]).unwrap();
+        assert_eq!((glider.width(), glider.height()), (3, 3));
+        assert_eq!(glider.get(0, 1), Cell::Alive);
+        assert_eq!(glider.get(0, 0), Cell::Dead);
+
+        assert!(Pattern::new(&[u32::MAX, 0]).is_err());
+        assert!(Pattern::new(&[60000, 60000]).is_err());
+        assert_eq!(Pattern::new(&[]).map(|empty| empty.width()), Ok(0));
+    }
+
+    #[test]
+    fn should_map_dihedral_transforms() {
+        assert_eq!(Transform::Rotate90.dimensions(2, 3), (3, 2));
+        assert_eq!(Transform::Rotate90.apply(0, 0, 2, 3), (0, 1));
+        assert_eq!(Transform::Rotate270.apply(0, 0, 2, 3), (2, 0));
+        assert_eq!(Transform::FlipAntiDiagonal.apply(0, 0, 2, 3), (2, 1));
+        assert_eq!(Transform::FlipDiagonal.apply(1, 2, 2, 3), (2, 1));
+    }
+}
@@ -0,0 +1,98 @@
+//Classic patterns built in, so demos don't have to spell out glider coordinates by hand
+
+use wasm_bindgen::prelude::*;
+use formats::parse_rle;
+use pattern::{Pattern, Transform};
+use universe::Universe;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NamedPattern {
+    Glider,
+    //Lightweight spaceship
+    Lwss,
+    Pulsar,
+    Pentadecathlon,
+    GosperGliderGun,
+    RPentomino,
+    Acorn,
+    Copperhead,
+}
+
+impl NamedPattern {
+    pub const ALL: [NamedPattern; 8] = [
+        NamedPattern::Glider, NamedPattern::Lwss, NamedPattern::Pulsar, NamedPattern::Pentadecathlon,
+        NamedPattern::GosperGliderGun, NamedPattern::RPentomino, NamedPattern::Acorn, NamedPattern::Copperhead,
+    ];
+
+    //Name as LifeWiki spells it
+    pub fn name(self) -> &'static str {
+        match self {
+            NamedPattern::Glider => "Glider",
+            NamedPattern::Lwss => "Lightweight spaceship",
+            NamedPattern::Pulsar => "Pulsar",
+            NamedPattern::Pentadecathlon => "Pentadecathlon",
+            NamedPattern::GosperGliderGun => "Gosper glider gun",
+            NamedPattern::RPentomino => "R-pentomino",
+            NamedPattern::Acorn => "Acorn",
+            NamedPattern::Copperhead => "Copperhead",
+        }
+    }
+
+    //Cells as RLE, all of them run under Conway's Life
+    pub fn rle(self) -> &'static str {
+        match self {
+            NamedPattern::Glider => "bo$2bo$3o!",
+            NamedPattern::Lwss => "bo2bo$o4b$o3bo$4o!",
+            NamedPattern::Pulsar => "2b3o3b3o2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2$2b3o3b3o$o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+            NamedPattern::Pentadecathlon => "2bo4bo$2ob4ob2o$2bo4bo!",
+            NamedPattern::GosperGliderGun => "24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!",
+            NamedPattern::RPentomino => "b2o$2o$bo!",
+            NamedPattern::Acorn => "bo$3bo$2o2b3o!",
+            NamedPattern::Copperhead => "b2o2b2o$3b2o$3b2o$obo2bobo$o6bo2$o6bo$b2o2b2o$2b4o2$3b2o$3b2o!",
+        }
+    }
+
+    pub fn pattern(self) -> Pattern {
+        parse_rle(self.rle()).and_then(|pattern| Pattern::from_coords(&pattern.cells)).expect("built in patterns are valid RLE")
+    }
+}
+
+//Name of a built in pattern for menus
+#[wasm_bindgen]
+pub fn named_pattern_name(pattern: NamedPattern) -> String {
+    pattern.name().to_string()
+}
+
+#[wasm_bindgen]
+impl Universe {
+    //Stamp a built in pattern with its top-left at (row, column), see stamp
+    pub fn insert_named(&mut self, pattern: NamedPattern, row: u32, column: u32) {
+        self.stamp(&pattern.pattern(), row, column, Transform::Identity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use formats::apgcode;
+
+    #[test]
+    fn should_parse_every_pattern() {
+        let codes: Vec<String> = NamedPattern::ALL.iter()
+            .map(|named| apgcode(&parse_rle(named.rle()).unwrap(), 30).unwrap_or_else(|e| e.to_string()))
+            .collect();
+        assert_eq!(&codes[..2], &["xq4_153", "xq4_6frc"]);
+        assert!(codes[2].starts_with("xp3_"));
+        assert!(codes[3].starts_with("xp15_"));
+        //The gun's gliders fly off, so it never comes back to its first phase
+        assert!(codes[4].contains("doesn't repeat"));
+        assert!(codes[7].starts_with("xq10_"));
+
+        let mut univ = Universe::empty(40, 20);
+        univ.insert_named(NamedPattern::GosperGliderGun, 1, 1);
+        assert_eq!(univ.population(), 36);
+        let populations: Vec<usize> = NamedPattern::ALL.iter().map(|named| parse_rle(named.rle()).unwrap().cells.len()).collect();
+        assert_eq!(populations, vec![5, 9, 48, 12, 36, 5, 7, 28]);
+    }
+}
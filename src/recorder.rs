@@ -0,0 +1,241 @@
+//Captures RGBA frames of a universe over a run and packs them as an animated PNG or a zip of PNG
+//files, for captures without the GIF palette and size limits
+
+use wasm_bindgen::prelude::*;
+use error::Error;
+use renderer::encode_png;
+use universe::Universe;
+
+//256 MiB of raw frames
+const DEFAULT_MEMORY_LIMIT: usize = 256 << 20;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Frame {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    cell_size: u32,
+    //Frames dropped after each kept one
+    skip: u32,
+    memory_limit: usize,
+    frames: Vec<Frame>,
+    //Captures asked for so far, kept or not
+    captures: u32,
+    bytes: usize,
+}
+
+#[wasm_bindgen]
+impl Recorder {
+    pub fn new(cell_size: u32) -> Recorder {
+        Recorder { cell_size: cell_size.max(1), skip: 0, memory_limit: DEFAULT_MEMORY_LIMIT, frames: Vec::new(), captures: 0, bytes: 0 }
+    }
+
+    //Keep one capture out of every skip + 1
+    pub fn set_frame_skip(&mut self, skip: u32) {
+        self.skip = skip;
+    }
+
+    //Bytes of raw frames to hold at most, captures past it are dropped
+    pub fn set_memory_limit(&mut self, bytes: usize) {
+        self.memory_limit = bytes;
+    }
+
+    pub fn frame_count(&self) -> u32 {
+        self.frames.len() as u32
+    }
+
+    //Bytes held by the frames
+    pub fn memory_usage(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.captures = 0;
+        self.bytes = 0;
+    }
+
+    //Render the universe as the next frame, returns whether it was kept: false for frames
+    //skipped and frames over the memory limit
+    pub fn capture(&mut self, universe: &Universe) -> bool {
+        self.captures += 1;
+        if !(self.captures - 1).is_multiple_of(self.skip.saturating_add(1)) {
+            return false;
+        }
+        let (width, height) = (universe.width() * self.cell_size, universe.height() * self.cell_size);
+        let size = width as usize * height as usize * 4;
+        if self.bytes + size > self.memory_limit {
+            return false;
+        }
+        self.frames.push(Frame { width, height, rgba: universe.render_rgba(self.cell_size) });
+        self.bytes += size;
+        true
+    }
+
+    //Capture the universe now and after each of the next generations ticks, returns the number of
+    //frames kept
+    pub fn record(&mut self, universe: &mut Universe, generations: u32) -> u32 {
+        let mut kept = self.capture(universe) as u32;
+        for _ in 0..generations {
+            universe.tick();
+            kept += self.capture(universe) as u32;
+        }
+        kept
+    }
+
+    #[wasm_bindgen(js_name = to_apng)]
+    pub fn to_apng_js(&self, frame_delay_ms: u16) -> Result<Vec<u8>, Error> {
+        self.to_apng(frame_delay_ms)
+    }
+
+    #[wasm_bindgen(js_name = to_zip)]
+    pub fn to_zip_js(&self) -> Result<Vec<u8>, Error> {
+        self.to_zip()
+    }
+}
+
+impl Recorder {
+    //Animated PNG of the frames shown frame_delay_ms apart and looping forever. Every frame has to
+    //be the same size, so don't resize the universe while recording one
+    pub fn to_apng(&self, frame_delay_ms: u16) -> Result<Vec<u8>, Error> {
+        let (width, height) = match self.frames.first() {
+            Some(frame) => (frame.width, frame.height),
+            None => return Err(Error::Invalid("no frames recorded".to_string())),
+        };
+        if self.frames.iter().any(|frame| (frame.width, frame.height) != (width, height)) {
+            return Err(Error::Invalid("frames differ in size".to_string()));
+        }
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = ::png::Encoder::new(&mut bytes, width, height);
+            encoder.set_color(::png::ColorType::Rgba);
+            encoder.set_depth(::png::BitDepth::Eight);
+            encoder.set_animated(self.frames.len() as u32, 0).map_err(|e| Error::Invalid(e.to_string()))?;
+            encoder.set_frame_delay(frame_delay_ms, 1000).map_err(|e| Error::Invalid(e.to_string()))?;
+            let mut writer = encoder.write_header().map_err(|e| Error::Invalid(e.to_string()))?;
+            for frame in &self.frames {
+                writer.write_image_data(&frame.rgba).map_err(|e| Error::Invalid(e.to_string()))?;
+            }
+            writer.finish().map_err(|e| Error::Invalid(e.to_string()))?;
+        }
+        Ok(bytes)
+    }
+
+    //Zip of frame_00000.png, frame_00001.png, .. stored as they are, PNG is compressed already
+    pub fn to_zip(&self) -> Result<Vec<u8>, Error> {
+        let files = self.frames.iter().enumerate()
+            .map(|(idx, frame)| Ok((format!("frame_{:05}.png", idx), encode_png(frame.width, frame.height, &frame.rgba)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(stored_zip(&files))
+    }
+}
+
+//Zip archive of uncompressed files, dated 1980-01-01
+fn stored_zip(files: &[(String, Vec<u8>)]) -> Vec<u8> {
+    fn put16(out: &mut Vec<u8>, value: u16) {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    fn put32(out: &mut Vec<u8>, value: u32) {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    //Version 2.0, no flags, stored, time 0, date 1980-01-01, crc, sizes, name length
+    fn header(out: &mut Vec<u8>, name: &str, data: &[u8]) {
+        for &field in &[20, 0, 0, 0, 0x21] {
+            put16(out, field);
+        }
+        put32(out, ::crc32fast::hash(data));
+        put32(out, data.len() as u32);
+        put32(out, data.len() as u32);
+        put16(out, name.len() as u16);
+        put16(out, 0);
+    }
+
+    let (mut zip, mut directory) = (Vec::new(), Vec::new());
+    for (name, data) in files {
+        let offset = zip.len() as u32;
+        put32(&mut zip, 0x0403_4b50);
+        header(&mut zip, name, data);
+        zip.extend_from_slice(name.as_bytes());
+        zip.extend_from_slice(data);
+
+        put32(&mut directory, 0x0201_4b50);
+        put16(&mut directory, 20);
+        header(&mut directory, name, data);
+        //Comment length, disk, internal and external attributes
+        for &field in &[0, 0, 0, 0, 0] {
+            put16(&mut directory, field);
+        }
+        put32(&mut directory, offset);
+        directory.extend_from_slice(name.as_bytes());
+    }
+    let (start, size) = (zip.len() as u32, directory.len() as u32);
+    zip.extend_from_slice(&directory);
+    put32(&mut zip, 0x0605_4b50);
+    for &field in &[0, 0, files.len() as u16, files.len() as u16] {
+        put16(&mut zip, field);
+    }
+    put32(&mut zip, size);
+    put32(&mut zip, start);
+    put16(&mut zip, 0);
+    zip
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blinker() -> Universe {
+        let mut univ = Universe::empty(5, 5);
+        univ.set_cells(&[(2, 1), (2, 2), (2, 3)]).unwrap();
+        univ
+    }
+
+    #[test]
+    fn should_skip_frames_and_respect_memory_limit() {
+        let mut univ = blinker();
+        let mut recorder = Recorder::new(2);
+        recorder.set_frame_skip(1);
+        assert_eq!(recorder.record(&mut univ, 4), 3);
+        assert_eq!(univ.generation(), 4);
+        assert_eq!(recorder.memory_usage(), 3 * 10 * 10 * 4);
+
+        recorder.clear();
+        recorder.set_memory_limit(2 * 10 * 10 * 4);
+        assert_eq!(recorder.record(&mut univ, 4), 2);
+        assert!(!recorder.capture(&univ));
+    }
+
+    #[test]
+    fn should_write_animated_png() {
+        let mut recorder = Recorder::new(1);
+        assert!(recorder.to_apng(100).is_err());
+        recorder.record(&mut blinker(), 2);
+        let bytes = recorder.to_apng(100).unwrap();
+        let mut reader = ::png::Decoder::new(&bytes[..]).read_info().unwrap();
+        assert_eq!(reader.info().animation_control().map(|control| control.num_frames), Some(3));
+        let mut frame = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut frame).unwrap();
+        assert_eq!(frame, recorder.frames[0].rgba);
+    }
+
+    #[test]
+    fn should_zip_png_frames() {
+        let mut recorder = Recorder::new(1);
+        recorder.record(&mut blinker(), 1);
+        let zip = recorder.to_zip().unwrap();
+        let png = encode_png(5, 5, &recorder.frames[0].rgba).unwrap();
+        assert_eq!(&zip[..4], &[0x50, 0x4b, 3, 4]);
+        assert_eq!(&zip[26..28], &[15, 0]);
+        assert_eq!(&zip[30..45], b"frame_00000.png");
+        assert_eq!(&zip[45..45 + png.len()], &png[..]);
+        //End record: two entries
+        let end = zip.len() - 22;
+        assert_eq!(&zip[end..end + 4], &[0x50, 0x4b, 5, 6]);
+        assert_eq!(&zip[end + 8..end + 12], &[2, 0, 2, 0]);
+    }
+}
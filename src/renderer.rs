@@ -0,0 +1,135 @@
+//Renderers drawing a Universe straight onto a canvas, so pages don't need their own drawing loop
+
+#[cfg(feature = "png-export")]
+use error::Error;
+use universe::Universe;
+
+mod canvas2d;
+mod offscreen;
+mod webgl;
+mod palette;
+
+pub use self::canvas2d::Canvas2d;
+pub use self::offscreen::Offscreen2d;
+pub use self::webgl::WebGl2;
+pub use self::palette::Palette;
+
+//0xRRGGBB as a CSS colour
+pub fn css_color(color: u32) -> String {
+    format!("#{:06x}", color & 0xff_ffff)
+}
+
+//0xRRGGBB as opaque RGBA bytes
+pub fn rgba(color: u32) -> [u8; 4] {
+    [(color >> 16) as u8, (color >> 8) as u8, color as u8, 0xff]
+}
+
+//PNG file of a width x height RGBA frame
+#[cfg(feature = "png-export")]
+pub fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    {
+        let mut encoder = ::png::Encoder::new(&mut bytes, width, height);
+        encoder.set_color(::png::ColorType::Rgba);
+        encoder.set_depth(::png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| Error::Invalid(e.to_string()))?;
+        writer.write_image_data(rgba).map_err(|e| Error::Invalid(e.to_string()))?;
+    }
+    Ok(bytes)
+}
+
+//Calls a 2D canvas context has to answer for draw_2d
+trait Context2d {
+    fn fill(&self, color: &str, x: f64, y: f64, width: f64, height: f64);
+    fn line(&self, color: &str, from: (f64, f64), to: (f64, f64));
+}
+
+//Cell size, colours and grid shared by the renderers, each falling back to the universe's
+#[derive(Debug, Clone, PartialEq)]
+struct Style {
+    cell_size: u32,
+    //0xRRGGBB per colour index (see Universe::color_indices), empty for Universe::color_table
+    colors: Vec<u32>,
+    //Colour of the one pixel lines between cells, None for the Palette's grid if any
+    grid: Option<u32>,
+}
+
+impl Style {
+    fn new(cell_size: u32) -> Style {
+        Style { cell_size: cell_size.max(1), colors: Vec::new(), grid: None }
+    }
+
+    fn grid(&self, universe: &Universe) -> Option<u32> {
+        self.grid.or_else(|| universe.custom_palette().and_then(|palette| palette.grid()))
+    }
+
+    //Pixels from one cell to the next
+    fn stride(&self, universe: &Universe) -> u32 {
+        self.cell_size + self.grid(universe).is_some() as u32
+    }
+
+    //Pixel size of the whole board
+    fn size(&self, universe: &Universe) -> (u32, u32) {
+        let (stride, border) = (self.stride(universe), self.grid(universe).is_some() as u32);
+        (universe.width() * stride + border, universe.height() * stride + border)
+    }
+
+    fn colors(&self, universe: &Universe) -> Vec<u32> {
+        if self.colors.is_empty() { universe.color_table() } else { self.colors.clone() }
+    }
+}
+
+//Background in the colour of state 0, then the grid, then the other colours one at a time
+fn draw_2d<C: Context2d>(context: &C, style: &Style, universe: &Universe) {
+    let (width, height) = style.size(universe);
+    let colors: Vec<String> = style.colors(universe).into_iter().map(css_color).collect();
+    context.fill(&colors[0], 0.0, 0.0, f64::from(width), f64::from(height));
+    let (stride, grid) = (style.stride(universe), style.grid(universe));
+    if let Some(grid) = grid {
+        let grid = css_color(grid);
+        for row in 0..=universe.height() {
+            let y = f64::from(row * stride) + 0.5;
+            context.line(&grid, (0.0, y), (f64::from(width), y));
+        }
+        for col in 0..=universe.width() {
+            let x = f64::from(col * stride) + 0.5;
+            context.line(&grid, (x, 0.0), (x, f64::from(height)));
+        }
+    }
+    //Cells by colour, indices past the end take the last one
+    let mut cells = vec![Vec::new(); colors.len()];
+    for (idx, color) in universe.color_indices().enumerate().filter(|&(_, color)| color > 0) {
+        cells[(color as usize).min(colors.len() - 1)].push(idx as u32);
+    }
+    let (offset, size, columns) = (grid.is_some() as u32, f64::from(style.cell_size), universe.width());
+    for (color, cells) in colors.iter().zip(&cells) {
+        for &idx in cells {
+            let (row, col) = (idx / columns, idx % columns);
+            context.fill(color, f64::from(col * stride + offset), f64::from(row * stride + offset), size, size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_lay_cells_out_with_grid() {
+        let mut univ = Universe::empty(4, 3);
+        let mut style = Style::new(5);
+        assert_eq!(style.size(&univ), (20, 15));
+        style.grid = Some(0x333333);
+        assert_eq!((style.stride(&univ), style.size(&univ)), (6, (25, 19)));
+        assert_eq!(style.colors(&univ), univ.palette());
+
+        let mut palette = Palette::new(0x000000, 0xffffff);
+        palette.set_grid(0x222222);
+        univ.set_palette(&palette);
+        style.grid = None;
+        assert_eq!((style.grid(&univ), style.size(&univ)), (Some(0x222222), (25, 19)));
+        assert_eq!(style.colors(&univ), vec![0x000000, 0xffffff]);
+        assert_eq!(css_color(0x00ff08), "#00ff08");
+        assert_eq!(rgba(0x123456), [0x12, 0x34, 0x56, 0xff]);
+    }
+}
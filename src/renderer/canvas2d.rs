@@ -0,0 +1,74 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+use universe::Universe;
+use error::Error;
+use super::{draw_2d, Context2d, Style};
+
+impl Context2d for CanvasRenderingContext2d {
+    fn fill(&self, color: &str, x: f64, y: f64, width: f64, height: f64) {
+        self.set_fill_style_str(color);
+        self.fill_rect(x, y, width, height);
+    }
+
+    fn line(&self, color: &str, from: (f64, f64), to: (f64, f64)) {
+        self.set_stroke_style_str(color);
+        self.begin_path();
+        self.move_to(from.0, from.1);
+        self.line_to(to.0, to.1);
+        self.stroke();
+    }
+}
+
+//Draws universes on a canvas element through its 2D context, one fillRect per cell that isn't
+//in state 0. The canvas is resized to fit the board on every draw
+#[wasm_bindgen]
+pub struct Canvas2d {
+    canvas: HtmlCanvasElement,
+    context: CanvasRenderingContext2d,
+    style: Style,
+}
+
+#[wasm_bindgen]
+impl Canvas2d {
+    pub fn new(canvas: HtmlCanvasElement, cell_size: u32) -> Result<Canvas2d, JsValue> {
+        let context = canvas.get_context("2d")?
+            .ok_or_else(|| JsValue::from(Error::Invalid("canvas has no 2d context".to_string())))?
+            .dyn_into::<CanvasRenderingContext2d>()?;
+        Ok(Canvas2d { canvas, context, style: Style::new(cell_size) })
+    }
+
+    pub fn cell_size(&self) -> u32 {
+        self.style.cell_size
+    }
+
+    //Pixels per cell side, at least 1
+    pub fn set_cell_size(&mut self, cell_size: u32) {
+        self.style.cell_size = cell_size.max(1);
+    }
+
+    //0xRRGGBB colour of each colour index (see Universe::color_indices), indices past the end
+    //take the last one. An empty list goes back to the universe's colours
+    pub fn set_colors(&mut self, colors: Vec<u32>) {
+        self.style.colors = colors;
+    }
+
+    //Draw one pixel lines of the given 0xRRGGBB colour between cells, whatever the Palette says
+    pub fn set_grid(&mut self, color: u32) {
+        self.style.grid = Some(color);
+    }
+
+    //Back to the Palette's grid, if any
+    pub fn reset_grid(&mut self) {
+        self.style.grid = None;
+    }
+
+    pub fn draw(&self, universe: &Universe) {
+        let (width, height) = self.style.size(universe);
+        if (self.canvas.width(), self.canvas.height()) != (width, height) {
+            self.canvas.set_width(width);
+            self.canvas.set_height(height);
+        }
+        draw_2d(&self.context, &self.style, universe);
+    }
+}
@@ -0,0 +1,92 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{ImageBitmap, OffscreenCanvas, OffscreenCanvasRenderingContext2d};
+use universe::Universe;
+use error::Error;
+use super::{draw_2d, Context2d, Style};
+
+impl Context2d for OffscreenCanvasRenderingContext2d {
+    fn fill(&self, color: &str, x: f64, y: f64, width: f64, height: f64) {
+        self.set_fill_style_str(color);
+        self.fill_rect(x, y, width, height);
+    }
+
+    fn line(&self, color: &str, from: (f64, f64), to: (f64, f64)) {
+        self.set_stroke_style_str(color);
+        self.begin_path();
+        self.move_to(from.0, from.1);
+        self.line_to(to.0, to.1);
+        self.stroke();
+    }
+}
+
+//Canvas2d for an OffscreenCanvas, so a simulation running in a worker can draw there too. The
+//frame callback is called with the generation after every draw; a canvas made with
+//transferControlToOffscreen shows the frame by itself, a standalone one can hand it over with
+//transfer_frame and postMessage
+#[wasm_bindgen]
+pub struct Offscreen2d {
+    canvas: OffscreenCanvas,
+    context: OffscreenCanvasRenderingContext2d,
+    style: Style,
+    on_frame: Option<js_sys::Function>,
+}
+
+#[wasm_bindgen]
+impl Offscreen2d {
+    pub fn new(canvas: OffscreenCanvas, cell_size: u32) -> Result<Offscreen2d, JsValue> {
+        let context = canvas.get_context("2d")?
+            .ok_or_else(|| JsValue::from(Error::Invalid("canvas has no 2d context".to_string())))?
+            .dyn_into::<OffscreenCanvasRenderingContext2d>()?;
+        Ok(Offscreen2d { canvas, context, style: Style::new(cell_size), on_frame: None })
+    }
+
+    pub fn cell_size(&self) -> u32 {
+        self.style.cell_size
+    }
+
+    pub fn set_cell_size(&mut self, cell_size: u32) {
+        self.style.cell_size = cell_size.max(1);
+    }
+
+    //See Canvas2d::set_colors
+    pub fn set_colors(&mut self, colors: Vec<u32>) {
+        self.style.colors = colors;
+    }
+
+    pub fn set_grid(&mut self, color: u32) {
+        self.style.grid = Some(color);
+    }
+
+    pub fn reset_grid(&mut self) {
+        self.style.grid = None;
+    }
+
+    //Called with the generation drawn once each frame is ready
+    pub fn set_on_frame(&mut self, callback: js_sys::Function) {
+        self.on_frame = Some(callback);
+    }
+
+    pub fn clear_on_frame(&mut self) {
+        self.on_frame = None;
+    }
+
+    //Errors are the ones the frame callback throws
+    pub fn draw(&self, universe: &Universe) -> Result<(), JsValue> {
+        let (width, height) = self.style.size(universe);
+        if (self.canvas.width(), self.canvas.height()) != (width, height) {
+            self.canvas.set_width(width);
+            self.canvas.set_height(height);
+        }
+        draw_2d(&self.context, &self.style, universe);
+        match self.on_frame {
+            Some(ref callback) => callback.call1(&JsValue::NULL, &JsValue::from(universe.generation())).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    //The last frame drawn as a transferable bitmap, which leaves the canvas blank
+    pub fn transfer_frame(&self) -> Result<ImageBitmap, JsValue> {
+        self.canvas.transfer_to_image_bitmap()
+    }
+}
@@ -0,0 +1,94 @@
+use wasm_bindgen::prelude::*;
+
+//Colours the built-in renderers use for a universe once set with Universe::set_palette: dead
+//cells (state 0), alive cells (state 1), the grid of the canvas renderers and age stops. With
+//age tracking on, a live cell takes the colour of the last stop whose age it has reached
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    dead: u32,
+    alive: u32,
+    grid: Option<u32>,
+    //(age, colour) by increasing age
+    stops: Vec<(u32, u32)>,
+}
+
+#[wasm_bindgen]
+impl Palette {
+    //0xRRGGBB colours
+    pub fn new(dead: u32, alive: u32) -> Palette {
+        Palette { dead, alive, grid: None, stops: Vec::new() }
+    }
+
+    pub fn dead(&self) -> u32 {
+        self.dead
+    }
+
+    pub fn set_dead(&mut self, color: u32) {
+        self.dead = color;
+    }
+
+    pub fn alive(&self) -> u32 {
+        self.alive
+    }
+
+    pub fn set_alive(&mut self, color: u32) {
+        self.alive = color;
+    }
+
+    pub fn grid(&self) -> Option<u32> {
+        self.grid
+    }
+
+    //Grid colour for canvas renderers that weren't given their own, see Canvas2d::set_grid
+    pub fn set_grid(&mut self, color: u32) {
+        self.grid = Some(color);
+    }
+
+    pub fn clear_grid(&mut self) {
+        self.grid = None;
+    }
+
+    //Live cells at least age generations old turn color, replacing a stop at the same age
+    pub fn add_age_stop(&mut self, age: u32, color: u32) {
+        match self.stops.binary_search_by_key(&age, |&(age, _)| age) {
+            Ok(idx) => self.stops[idx].1 = color,
+            Err(idx) => self.stops.insert(idx, (age, color)),
+        }
+    }
+
+    pub fn clear_age_stops(&mut self) {
+        self.stops.clear();
+    }
+
+    //Packed [age0, color0, age1, color1, ...]
+    pub fn age_stops(&self) -> Vec<u32> {
+        self.stops.iter().flat_map(|&(age, color)| vec![age, color]).collect()
+    }
+}
+
+impl Palette {
+    //Which stop colours a cell of age, None before the first stop
+    pub fn stop(&self, age: u32) -> Option<usize> {
+        self.stops.iter().rposition(|&(from, _)| age >= from)
+    }
+
+    pub fn stop_colors<'a>(&'a self) -> impl Iterator<Item = u32> + 'a {
+        self.stops.iter().map(|&(_, color)| color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_keep_age_stops_sorted() {
+        let mut palette = Palette::new(0, 0xffffff);
+        palette.add_age_stop(10, 0xff0000);
+        palette.add_age_stop(2, 0x00ff00);
+        palette.add_age_stop(10, 0x0000ff);
+        assert_eq!(palette.age_stops(), vec![2, 0x00ff00, 10, 0x0000ff]);
+        assert_eq!((palette.stop(1), palette.stop(2), palette.stop(9), palette.stop(50)), (None, Some(0), Some(0), Some(1)));
+    }
+}
@@ -0,0 +1,178 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlCanvasElement, WebGl2RenderingContext as Gl, WebGlProgram, WebGlShader, WebGlTexture, WebGlUniformLocation};
+use universe::Universe;
+use error::Error;
+use super::{rgba, Style};
+
+//Full canvas quad made from the vertex ids, no buffers needed
+const VERTEX_SHADER: &str = r#"#version 300 es
+void main() {
+    gl_Position = vec4(vec2(gl_VertexID & 1, gl_VertexID >> 1) * 2.0 - 1.0, 0.0, 1.0);
+}
+"#;
+
+//Each pixel looks up its cell's colour index in the cells texture and the colour in the palette
+//texture, grid lines fall on multiples of the stride
+const FRAGMENT_SHADER: &str = r#"#version 300 es
+precision highp float;
+precision highp usampler2D;
+uniform usampler2D cells;
+uniform sampler2D palette;
+uniform float stride;
+uniform bool grid;
+uniform vec3 grid_color;
+uniform float height;
+out vec4 color;
+void main() {
+    vec2 pixel = vec2(gl_FragCoord.x, height - gl_FragCoord.y);
+    vec2 offset = mod(pixel, stride);
+    if (grid && (offset.x < 1.0 || offset.y < 1.0)) {
+        color = vec4(grid_color, 1.0);
+        return;
+    }
+    ivec2 cell = min(ivec2(pixel / stride), textureSize(cells, 0) - 1);
+    int state = int(texelFetch(cells, cell, 0).r);
+    color = texelFetch(palette, ivec2(min(state, textureSize(palette, 0).x - 1), 0), 0);
+}
+"#;
+
+struct Uniforms {
+    cells: Option<WebGlUniformLocation>,
+    palette: Option<WebGlUniformLocation>,
+    stride: Option<WebGlUniformLocation>,
+    grid: Option<WebGlUniformLocation>,
+    grid_color: Option<WebGlUniformLocation>,
+    height: Option<WebGlUniformLocation>,
+}
+
+//Draws universes with WebGL2: the cell colour indices go up as a one byte per cell texture every frame and
+//a single quad colours every pixel in the fragment shader, so drawing costs the same however many
+//cells are alive
+#[wasm_bindgen]
+pub struct WebGl2 {
+    canvas: HtmlCanvasElement,
+    gl: Gl,
+    program: WebGlProgram,
+    cells: WebGlTexture,
+    palette: WebGlTexture,
+    uniforms: Uniforms,
+    style: Style,
+    //Upload buffer reused between frames
+    bytes: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl WebGl2 {
+    pub fn new(canvas: HtmlCanvasElement, cell_size: u32) -> Result<WebGl2, JsValue> {
+        let gl = canvas.get_context("webgl2")?
+            .ok_or_else(|| JsValue::from(Error::Invalid("canvas has no webgl2 context".to_string())))?
+            .dyn_into::<Gl>()?;
+        let program = link(&gl)?;
+        let uniform = |name: &str| gl.get_uniform_location(&program, name);
+        let uniforms = Uniforms {
+            cells: uniform("cells"),
+            palette: uniform("palette"),
+            stride: uniform("stride"),
+            grid: uniform("grid"),
+            grid_color: uniform("grid_color"),
+            height: uniform("height"),
+        };
+        let (cells, palette) = (texture(&gl)?, texture(&gl)?);
+        Ok(WebGl2 { canvas, gl, program, cells, palette, uniforms, style: Style::new(cell_size), bytes: Vec::new() })
+    }
+
+    pub fn cell_size(&self) -> u32 {
+        self.style.cell_size
+    }
+
+    pub fn set_cell_size(&mut self, cell_size: u32) {
+        self.style.cell_size = cell_size.max(1);
+    }
+
+    //See Canvas2d::set_colors
+    pub fn set_colors(&mut self, colors: Vec<u32>) {
+        self.style.colors = colors;
+    }
+
+    pub fn set_grid(&mut self, color: u32) {
+        self.style.grid = Some(color);
+    }
+
+    pub fn reset_grid(&mut self) {
+        self.style.grid = None;
+    }
+
+    pub fn draw(&mut self, universe: &Universe) -> Result<(), JsValue> {
+        let (width, height) = self.style.size(universe);
+        if (self.canvas.width(), self.canvas.height()) != (width, height) {
+            self.canvas.set_width(width);
+            self.canvas.set_height(height);
+        }
+        let gl = &self.gl;
+        gl.viewport(0, 0, width as i32, height as i32);
+        gl.use_program(Some(&self.program));
+        gl.pixel_storei(Gl::UNPACK_ALIGNMENT, 1);
+
+        self.bytes.clear();
+        self.bytes.extend(universe.color_indices());
+        gl.active_texture(Gl::TEXTURE0);
+        gl.bind_texture(Gl::TEXTURE_2D, Some(&self.cells));
+        gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(Gl::TEXTURE_2D, 0, Gl::R8UI as i32,
+            universe.width() as i32, universe.height() as i32, 0, Gl::RED_INTEGER, Gl::UNSIGNED_BYTE, Some(&self.bytes))?;
+
+        let colors = self.style.colors(universe);
+        let palette: Vec<u8> = colors.iter().flat_map(|&color| rgba(color).to_vec()).collect();
+        gl.active_texture(Gl::TEXTURE1);
+        gl.bind_texture(Gl::TEXTURE_2D, Some(&self.palette));
+        gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(Gl::TEXTURE_2D, 0, Gl::RGBA8 as i32,
+            colors.len() as i32, 1, 0, Gl::RGBA, Gl::UNSIGNED_BYTE, Some(&palette))?;
+
+        let uniforms = &self.uniforms;
+        let grid = self.style.grid(universe);
+        let (stride, grid_color) = (self.style.stride(universe), rgba(grid.unwrap_or(0)));
+        gl.uniform1i(uniforms.cells.as_ref(), 0);
+        gl.uniform1i(uniforms.palette.as_ref(), 1);
+        gl.uniform1f(uniforms.stride.as_ref(), stride as f32);
+        gl.uniform1i(uniforms.grid.as_ref(), grid.is_some() as i32);
+        gl.uniform3f(uniforms.grid_color.as_ref(), f32::from(grid_color[0]) / 255.0, f32::from(grid_color[1]) / 255.0,
+            f32::from(grid_color[2]) / 255.0);
+        gl.uniform1f(uniforms.height.as_ref(), height as f32);
+        gl.draw_arrays(Gl::TRIANGLE_STRIP, 0, 4);
+        Ok(())
+    }
+}
+
+//Texture sampled texel by texel, integer textures can't be filtered anyway
+fn texture(gl: &Gl) -> Result<WebGlTexture, JsValue> {
+    let texture = gl.create_texture().ok_or_else(|| JsValue::from(Error::Invalid("can't create texture".to_string())))?;
+    gl.bind_texture(Gl::TEXTURE_2D, Some(&texture));
+    for &(param, value) in &[(Gl::TEXTURE_MIN_FILTER, Gl::NEAREST), (Gl::TEXTURE_MAG_FILTER, Gl::NEAREST),
+        (Gl::TEXTURE_WRAP_S, Gl::CLAMP_TO_EDGE), (Gl::TEXTURE_WRAP_T, Gl::CLAMP_TO_EDGE)] {
+        gl.tex_parameteri(Gl::TEXTURE_2D, param, value as i32);
+    }
+    Ok(texture)
+}
+
+fn compile(gl: &Gl, kind: u32, source: &str) -> Result<WebGlShader, Error> {
+    let shader = gl.create_shader(kind).ok_or_else(|| Error::Invalid("can't create shader".to_string()))?;
+    gl.shader_source(&shader, source);
+    gl.compile_shader(&shader);
+    if gl.get_shader_parameter(&shader, Gl::COMPILE_STATUS).as_bool().unwrap_or(false) {
+        Ok(shader)
+    } else {
+        Err(Error::Invalid(gl.get_shader_info_log(&shader).unwrap_or_else(|| "shader didn't compile".to_string())))
+    }
+}
+
+fn link(gl: &Gl) -> Result<WebGlProgram, Error> {
+    let program = gl.create_program().ok_or_else(|| Error::Invalid("can't create program".to_string()))?;
+    gl.attach_shader(&program, &compile(gl, Gl::VERTEX_SHADER, VERTEX_SHADER)?);
+    gl.attach_shader(&program, &compile(gl, Gl::FRAGMENT_SHADER, FRAGMENT_SHADER)?);
+    gl.link_program(&program);
+    if gl.get_program_parameter(&program, Gl::LINK_STATUS).as_bool().unwrap_or(false) {
+        Ok(program)
+    } else {
+        Err(Error::Invalid(gl.get_program_info_log(&program).unwrap_or_else(|| "program didn't link".to_string())))
+    }
+}
@@ -0,0 +1,724 @@
+use wasm_bindgen::prelude::*;
+use std::fmt;
+use std::str::FromStr;
+use universe::Cell;
+use error::Error;
+use hensel::{self, ConfigSet};
+
+//Well known Life-like rules, so frontends can list them without hardcoding rule strings
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RulePreset {
+    Conway,
+    HighLife,
+    Seeds,
+    DayAndNight,
+    LifeWithoutDeath,
+    Maze,
+    Replicator,
+    TwoByTwo,
+    Diamoeba,
+    Morley,
+    Anneal,
+    BriansBrain,
+    StarWars,
+}
+
+//Well known block rules for the Margolus mode
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockPreset {
+    Critters,
+    BilliardBall,
+    Tron,
+}
+
+//Row and column offsets of the eight Moore neighbours, read row by row
+pub const MOORE_OFFSETS: [(i32, i32); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+//Which cells around a cell count as its neighbours
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Neighbourhood {
+    //The eight surrounding cells
+    Moore,
+    //Only the four orthogonal cells
+    VonNeumann,
+    //Six cells of a hexagonal grid laid out on the square one: the Moore neighbours except the
+    //top-right and bottom-left corners, each row being half a cell left of the one above
+    Hexagonal,
+}
+
+impl Neighbourhood {
+    pub fn size(self) -> usize {
+        match self {
+            Neighbourhood::Moore => 8,
+            Neighbourhood::VonNeumann => 4,
+            Neighbourhood::Hexagonal => 6,
+        }
+    }
+
+    //Suffix marking the neighbourhood at the end of a rule string
+    fn suffix(self) -> &'static str {
+        match self {
+            Neighbourhood::Moore => "",
+            Neighbourhood::VonNeumann => "V",
+            Neighbourhood::Hexagonal => "H",
+        }
+    }
+
+    //Whether the cell at the given row and column offset (each -1, 0 or 1) is a neighbour
+    pub fn contains(self, rows: i32, columns: i32) -> bool {
+        match self {
+            Neighbourhood::Moore => rows != 0 || columns != 0,
+            Neighbourhood::VonNeumann => (rows == 0) != (columns == 0),
+            Neighbourhood::Hexagonal => rows + columns != 0,
+        }
+    }
+}
+
+//Life-like outer totalistic rule, bit n of birth/survival is set when n live neighbours
+//make a dead cell come alive / keep a live cell alive.
+//Generations rules have more than two states: a live cell that does not survive goes
+//through the dying states 2..states-1 before it is dead again.
+//Isotropic non-totalistic rules ("B2-a/S12") also keep the exact Moore configurations that give
+//births and survivals, the masks then only tell which counts have any
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rule {
+    birth: u16,
+    survival: u16,
+    states: u8,
+    neighbourhood: Neighbourhood,
+    configs: Option<(ConfigSet, ConfigSet)>,
+}
+
+fn mask(counts: &[u8]) -> u16 {
+    counts.iter().fold(0u16, |mask, &n| mask | 1 << n)
+}
+
+impl Rule {
+    pub fn new(birth: &[u8], survival: &[u8]) -> Rule {
+        Rule { birth: mask(birth), survival: mask(survival), states: 2, neighbourhood: Neighbourhood::Moore, configs: None }
+    }
+
+    //Generations rule with the given number of states, at least 2
+    pub fn generations(birth: &[u8], survival: &[u8], states: u8) -> Rule {
+        Rule { birth: mask(birth), survival: mask(survival), states: states.max(2), neighbourhood: Neighbourhood::Moore,
+            configs: None }
+    }
+
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    //Hensel letters only make sense for the Moore neighbourhood, other ones fall back to the counts
+    pub fn with_neighbourhood(self, neighbourhood: Neighbourhood) -> Rule {
+        let configs = if neighbourhood == Neighbourhood::Moore { self.configs } else { None };
+        Rule { neighbourhood, configs, ..self }
+    }
+
+    //Whether births and survivals depend on where the neighbours are and not just on their number
+    pub fn is_isotropic(&self) -> bool {
+        self.configs.is_some()
+    }
+
+    //Rule from a 2x9 totalistic table: entry state * 9 + neighbours is the next state (0 or 1)
+    //of a dead (state 0) or alive (state 1) cell with that many live Moore neighbours
+    pub fn from_table(table: &[u8]) -> Result<Rule, Error> {
+        if table.len() != 18 {
+            return Err(Error::Invalid(format!("rule table needs 18 entries (2 states x 9 counts), got {}", table.len())));
+        }
+        if let Some(&state) = table.iter().find(|&&state| state > 1) {
+            return Err(Error::Invalid(format!("invalid next state {} in rule table", state)));
+        }
+        let counts = |row: &[u8]| (0..9u8).filter(|&n| row[n as usize] == 1).collect::<Vec<u8>>();
+        Ok(Rule::new(&counts(&table[..9]), &counts(&table[9..])))
+    }
+
+    //Inverse of from_table, only meaningful for two state Moore rules
+    pub fn table(&self) -> Vec<u8> {
+        (0..9).map(|n| self.is_birth(n) as u8)
+            .chain((0..9).map(|n| self.is_survival(n) as u8))
+            .collect()
+    }
+
+    //B3/S23
+    pub fn conway() -> Rule {
+        Rule::new(&[3], &[2, 3])
+    }
+
+    pub fn is_birth(&self, neighbours: usize) -> bool {
+        self.birth & (1 << neighbours) != 0
+    }
+
+    pub fn is_survival(&self, neighbours: usize) -> bool {
+        self.survival & (1 << neighbours) != 0
+    }
+
+    //Same as is_birth/is_survival for a Moore configuration (see the hensel module)
+    pub fn is_birth_config(&self, config: u8) -> bool {
+        match self.configs {
+            Some((birth, _)) => birth.contains(config),
+            None => self.is_birth(config.count_ones() as usize),
+        }
+    }
+
+    pub fn is_survival_config(&self, config: u8) -> bool {
+        match self.configs {
+            Some((_, survival)) => survival.contains(config),
+            None => self.is_survival(config.count_ones() as usize),
+        }
+    }
+
+    pub fn next(&self, cell: Cell, neighbours: usize) -> Cell {
+        let alive = match cell {
+            Cell::Dead => self.is_birth(neighbours),
+            Cell::Alive => self.is_survival(neighbours),
+        };
+        if alive { Cell::Alive } else { Cell::Dead }
+    }
+
+    pub fn next_config(&self, cell: Cell, config: u8) -> Cell {
+        let alive = match cell {
+            Cell::Dead => self.is_birth_config(config),
+            Cell::Alive => self.is_survival_config(config),
+        };
+        if alive { Cell::Alive } else { Cell::Dead }
+    }
+
+    //Next state from the byte of all eight Moore neighbours (see the hensel module), only the
+    //ones in the rule's neighbourhood are counted
+    pub fn next_from_moore(&self, cell: Cell, config: u8) -> Cell {
+        if self.neighbourhood == Neighbourhood::Moore {
+            return self.next_config(cell, config);
+        }
+        let counted = MOORE_OFFSETS.iter()
+            .fold(0u8, |mask, &(r, c)| mask << 1 | self.neighbourhood.contains(r, c) as u8);
+        self.next(cell, (config & counted).count_ones() as usize)
+    }
+
+    //Transition of a cell state for Generations rules, only state 1 counts as alive
+    pub fn next_state(&self, state: u8, neighbours: usize) -> u8 {
+        self.advance(state, self.is_birth(neighbours), self.is_survival(neighbours))
+    }
+
+    pub fn next_state_config(&self, state: u8, config: u8) -> u8 {
+        self.advance(state, self.is_birth_config(config), self.is_survival_config(config))
+    }
+
+    fn advance(&self, state: u8, birth: bool, survival: bool) -> u8 {
+        match state {
+            0 if birth => 1,
+            0 => 0,
+            1 if survival => 1,
+            dying if dying + 1 < self.states => dying + 1,
+            _ => 0,
+        }
+    }
+}
+
+impl From<RulePreset> for Rule {
+    fn from(preset: RulePreset) -> Rule {
+        match preset {
+            RulePreset::Conway => Rule::conway(),
+            RulePreset::HighLife => Rule::new(&[3, 6], &[2, 3]),
+            RulePreset::Seeds => Rule::new(&[2], &[]),
+            RulePreset::DayAndNight => Rule::new(&[3, 6, 7, 8], &[3, 4, 6, 7, 8]),
+            RulePreset::LifeWithoutDeath => Rule::new(&[3], &[0, 1, 2, 3, 4, 5, 6, 7, 8]),
+            RulePreset::Maze => Rule::new(&[3], &[1, 2, 3, 4, 5]),
+            RulePreset::Replicator => Rule::new(&[1, 3, 5, 7], &[1, 3, 5, 7]),
+            RulePreset::TwoByTwo => Rule::new(&[3, 6], &[1, 2, 5]),
+            RulePreset::Diamoeba => Rule::new(&[3, 5, 6, 7, 8], &[5, 6, 7, 8]),
+            RulePreset::Morley => Rule::new(&[3, 6, 8], &[2, 4, 5]),
+            RulePreset::Anneal => Rule::new(&[4, 6, 7, 8], &[3, 5, 6, 7, 8]),
+            RulePreset::BriansBrain => Rule::generations(&[2], &[], 3),
+            RulePreset::StarWars => Rule::generations(&[2], &[3, 4, 5], 4),
+        }
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Rule {
+        Rule::conway()
+    }
+}
+
+//Counts of a birth or survival part, each optionally followed by Hensel letters ("2ak") or by a
+//minus and the letters it excludes ("2-a"). Returns the count mask, the matching configurations
+//and whether any letters showed up
+fn parse_counts(digits: &str) -> Result<(u16, ConfigSet, bool), Error> {
+    let (mut mask, mut set, mut letters) = (0u16, ConfigSet::default(), false);
+    let mut chars = digits.chars().peekable();
+    while let Some(ch) = chars.next() {
+        let count = match ch.to_digit(10) {
+            Some(n) if n <= 8 => n,
+            _ => return Err(Error::Invalid(format!("invalid neighbour count '{}'", ch))),
+        };
+        let negated = chars.peek() == Some(&'-');
+        if negated {
+            chars.next();
+        }
+        let mut group = if negated { ConfigSet::with_count(count) } else { ConfigSet::default() };
+        let mut listed = false;
+        while let Some(&letter) = chars.peek().filter(|ch| ch.is_ascii_alphabetic()) {
+            chars.next();
+            let configs = hensel::configs(count, letter.to_ascii_lowercase())
+                .ok_or_else(|| Error::Invalid(format!("invalid Hensel letter '{}' after {}", letter, count)))?;
+            for config in (0..=255u8).filter(|&config| configs.contains(config)) {
+                if negated { group.remove(config) } else { group.insert(config) }
+            }
+            listed = true;
+        }
+        if negated && !listed {
+            return Err(Error::Invalid(format!("missing Hensel letters after '{}-'", count)));
+        }
+        if !listed {
+            group = ConfigSet::with_count(count);
+        }
+        letters |= listed;
+        if !group.is_empty() {
+            mask |= 1 << count;
+        }
+        for config in (0..=255u8).filter(|&config| group.contains(config)) {
+            set.insert(config);
+        }
+    }
+    Ok((mask, set, letters))
+}
+
+//One part of a rule in the shortest Hensel form: the count alone when every letter is in, else
+//the letters that are in or a minus and the ones that are out
+fn write_counts(f: &mut fmt::Formatter, configs: &ConfigSet) -> fmt::Result {
+    for count in 0..=8 {
+        let (included, excluded): (String, String) = hensel::letters(count).chars()
+            .partition(|&letter| hensel::configs(count, letter)
+                .is_some_and(|set| (0..=255u8).any(|config| set.contains(config) && configs.contains(config))));
+        match (included.len(), excluded.len()) {
+            (0, _) => {}
+            (_, 0) => write!(f, "{}", count)?,
+            (i, e) if i <= e => write!(f, "{}{}", count, included)?,
+            _ => write!(f, "{}-{}", count, excluded)?,
+        }
+    }
+    Ok(())
+}
+
+fn parse_state_count(part: &str) -> Result<u8, Error> {
+    let digits = part.trim_start_matches(&['C', 'c', 'G', 'g'][..]);
+    match digits.parse::<u8>() {
+        Ok(states) if states >= 2 => Ok(states),
+        _ => Err(Error::Invalid(format!("invalid number of states '{}'", part))),
+    }
+}
+
+//Accepts "B36/S23" (in either order, any case) as well as the older "23/36" survival/birth form.
+//Generations rules add the number of states as a third part: "B2/S/C3", "B2/S/3" or "/2/3",
+//a trailing V or H selects the von Neumann or hexagonal neighbourhood: "B2/S013V", "B2/S34H",
+//and Moore counts may carry Hensel letters for isotropic non-totalistic rules: "B2-a/S12"
+impl FromStr for Rule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Rule, Error> {
+        let trimmed = s.trim();
+        let (trimmed, neighbourhood) = match trimmed.chars().last() {
+            Some('V') | Some('v') => (&trimmed[..trimmed.len() - 1], Neighbourhood::VonNeumann),
+            Some('H') | Some('h') => (&trimmed[..trimmed.len() - 1], Neighbourhood::Hexagonal),
+            _ => (trimmed, Neighbourhood::Moore),
+        };
+        let parts: Vec<&str> = trimmed.split('/').collect();
+        if parts.len() != 2 && parts.len() != 3 {
+            return Err(Error::Invalid(format!("rule '{}' should have two or three parts separated by '/'", s)));
+        }
+        let states = match parts.get(2) {
+            Some(part) => parse_state_count(part.trim())?,
+            None => 2,
+        };
+        let (mut birth, mut survival) = (None, None);
+        for (i, part) in parts[..2].iter().enumerate() {
+            let part = part.trim();
+            match part.chars().next() {
+                Some('B') | Some('b') => birth = Some(parse_counts(&part[1..])?),
+                Some('S') | Some('s') => survival = Some(parse_counts(&part[1..])?),
+                _ if i == 0 => survival = Some(parse_counts(part)?),
+                _ => birth = Some(parse_counts(part)?),
+            }
+        }
+        let ((birth, birth_configs, birth_letters), (survival, survival_configs, survival_letters)) = match (birth, survival) {
+            (Some(birth), Some(survival)) => (birth, survival),
+            _ => return Err(Error::Invalid(format!("rule '{}' needs both a birth and a survival part", s))),
+        };
+        if (birth | survival) >> (neighbourhood.size() + 1) != 0 {
+            return Err(Error::Invalid(format!("rule '{}' counts more than {} neighbours", s, neighbourhood.size())));
+        }
+        let configs = if birth_letters || survival_letters {
+            if neighbourhood != Neighbourhood::Moore {
+                return Err(Error::Invalid(format!("rule '{}' uses Hensel letters outside the Moore neighbourhood", s)));
+            }
+            Some((birth_configs, survival_configs))
+        } else {
+            None
+        };
+        Ok(Rule { birth, survival, states, neighbourhood, configs })
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some((birth, survival)) = self.configs {
+            write!(f, "B")?;
+            write_counts(f, &birth)?;
+            write!(f, "/S")?;
+            write_counts(f, &survival)?;
+        } else {
+            write!(f, "B")?;
+            for n in (0..9).filter(|&n| self.is_birth(n)) {
+                write!(f, "{}", n)?;
+            }
+            write!(f, "/S")?;
+            for n in (0..9).filter(|&n| self.is_survival(n)) {
+                write!(f, "{}", n)?;
+            }
+        }
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+        write!(f, "{}", self.neighbourhood.suffix())
+    }
+}
+
+//Larger than Life rule: neighbours are counted over the (2 * radius + 1)^2 square around a cell,
+//optionally including the cell itself, and birth/survival are inclusive ranges of that count
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LtlRule {
+    radius: u32,
+    include_center: bool,
+    birth: (u32, u32),
+    survival: (u32, u32),
+}
+
+pub const MAX_LTL_RADIUS: u32 = 10;
+
+impl LtlRule {
+    pub fn new(radius: u32, include_center: bool, birth: (u32, u32), survival: (u32, u32)) -> LtlRule {
+        LtlRule { radius: radius.clamp(1, MAX_LTL_RADIUS), include_center, birth, survival }
+    }
+
+    //R5,C0,M1,S34..58,B34..45,NM
+    pub fn bosco() -> LtlRule {
+        LtlRule::new(5, true, (34, 45), (34, 58))
+    }
+
+    pub fn radius(&self) -> u32 {
+        self.radius
+    }
+
+    pub fn include_center(&self) -> bool {
+        self.include_center
+    }
+
+    pub fn next(&self, cell: Cell, neighbours: u32) -> Cell {
+        let (min, max) = match cell {
+            Cell::Dead => self.birth,
+            Cell::Alive => self.survival,
+        };
+        if min <= neighbours && neighbours <= max { Cell::Alive } else { Cell::Dead }
+    }
+}
+
+impl Default for LtlRule {
+    fn default() -> LtlRule {
+        LtlRule::bosco()
+    }
+}
+
+//Margolus block rule: the grid is cut into 2x2 blocks, shifted by one cell diagonally every other
+//generation, and each block is replaced as a whole. Blocks are 4 bit numbers, top-left cell in
+//bit 3, top-right in bit 2, bottom-left in bit 1 and bottom-right in bit 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockRule {
+    table: [u8; 16],
+}
+
+//Half turn of a block
+fn rotate_block(block: u8) -> u8 {
+    (block & 1) << 3 | (block & 2) << 1 | (block & 4) >> 1 | (block & 8) >> 3
+}
+
+impl BlockRule {
+    //table[block] is what the block turns into, blocks past 15 are rejected
+    pub fn new(table: &[u8]) -> Result<BlockRule, Error> {
+        if table.len() != 16 {
+            return Err(Error::Invalid(format!("block rule needs 16 entries, got {}", table.len())));
+        }
+        if let Some(&block) = table.iter().find(|&&block| block > 15) {
+            return Err(Error::Invalid(format!("invalid block {} in block rule", block)));
+        }
+        let mut rule = BlockRule { table: [0; 16] };
+        rule.table.copy_from_slice(table);
+        Ok(rule)
+    }
+
+    fn from_fn<F: Fn(u8) -> u8>(f: F) -> BlockRule {
+        let mut table = [0; 16];
+        for (block, next) in table.iter_mut().enumerate() {
+            *next = f(block as u8);
+        }
+        BlockRule { table }
+    }
+
+    //Blocks with two live cells stay, the others are inverted and those with three also turned
+    //half way round. Reversible and Life-like
+    pub fn critters() -> BlockRule {
+        BlockRule::from_fn(|block| match block.count_ones() {
+            2 => block,
+            3 => rotate_block(!block & 0xf),
+            _ => !block & 0xf,
+        })
+    }
+
+    //Billiard ball machine: a lone cell moves to the opposite corner and two cells on a diagonal
+    //swap to the other diagonal, everything else stays
+    pub fn billiard_ball() -> BlockRule {
+        BlockRule::from_fn(|block| match block {
+            0b1001 => 0b0110,
+            0b0110 => 0b1001,
+            single if single.count_ones() == 1 => rotate_block(single),
+            other => other,
+        })
+    }
+
+    //Empty and full blocks are inverted, the rest stays
+    pub fn tron() -> BlockRule {
+        BlockRule::from_fn(|block| match block {
+            0 => 0xf,
+            0xf => 0,
+            other => other,
+        })
+    }
+
+    pub fn next(&self, block: u8) -> u8 {
+        self.table[block as usize & 0xf]
+    }
+
+    pub fn table(&self) -> &[u8] {
+        &self.table
+    }
+}
+
+impl From<BlockPreset> for BlockRule {
+    fn from(preset: BlockPreset) -> BlockRule {
+        match preset {
+            BlockPreset::Critters => BlockRule::critters(),
+            BlockPreset::BilliardBall => BlockRule::billiard_ball(),
+            BlockPreset::Tron => BlockRule::tron(),
+        }
+    }
+}
+
+impl Default for BlockRule {
+    fn default() -> BlockRule {
+        BlockRule::critters()
+    }
+}
+
+//Cyclic automaton: a cell in state n moves on to state n + 1 (wrapping to 0 after the last one)
+//once at least threshold of its Moore neighbours are already in that state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CyclicRule {
+    states: u8,
+    threshold: u8,
+}
+
+impl CyclicRule {
+    //At least 3 states, threshold between 1 and 8
+    pub fn new(states: u8, threshold: u8) -> CyclicRule {
+        CyclicRule { states: states.max(3), threshold: threshold.clamp(1, 8) }
+    }
+
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    //State a cell moves to when it gets eaten
+    pub fn successor(&self, state: u8) -> u8 {
+        (state + 1) % self.states
+    }
+
+    pub fn next(&self, state: u8, eaters: usize) -> u8 {
+        if eaters >= self.threshold as usize { self.successor(state) } else { state }
+    }
+}
+
+//14 states eaten by a single neighbour, which settles into spirals from random soups
+impl Default for CyclicRule {
+    fn default() -> CyclicRule {
+        CyclicRule::new(14, 1)
+    }
+}
+
+fn parse_range(part: &str) -> Result<(u32, u32), Error> {
+    let bounds: Vec<&str> = part.split("..").collect();
+    let parse = |bound: &str| bound.parse::<u32>().map_err(|_| Error::Invalid(format!("invalid range '{}'", part)));
+    match bounds.len() {
+        1 => parse(bounds[0]).map(|n| (n, n)),
+        2 => Ok((parse(bounds[0])?, parse(bounds[1])?)),
+        _ => Err(Error::Invalid(format!("invalid range '{}'", part))),
+    }
+}
+
+//Golly's notation, e.g. "R5,C0,M1,S34..58,B34..45,NM". Only two state rules over the
+//Moore neighbourhood are supported
+impl FromStr for LtlRule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<LtlRule, Error> {
+        let (mut radius, mut include_center, mut birth, mut survival) = (None, false, None, None);
+        for part in s.trim().split(',').map(str::trim) {
+            let value = part.get(1..).unwrap_or("");
+            match part.chars().next().map(|ch| ch.to_ascii_uppercase()) {
+                Some('R') => radius = Some(value.parse::<u32>().map_err(|_| Error::Invalid(format!("invalid radius '{}'", part)))?),
+                Some('C') if value == "0" || value == "2" => {}
+                Some('C') => return Err(Error::Invalid(format!("multi-state Larger than Life rules are not supported: '{}'", part))),
+                Some('M') => include_center = value == "1",
+                Some('S') => survival = Some(parse_range(value)?),
+                Some('B') => birth = Some(parse_range(value)?),
+                Some('N') if value.eq_ignore_ascii_case("M") => {}
+                Some('N') => return Err(Error::Invalid(format!("only the Moore neighbourhood (NM) is supported: '{}'", part))),
+                _ => return Err(Error::Invalid(format!("unexpected '{}' in Larger than Life rule", part))),
+            }
+        }
+        match (radius, birth, survival) {
+            (Some(radius), Some(birth), Some(survival)) if (1..=MAX_LTL_RADIUS).contains(&radius) =>
+                Ok(LtlRule { radius, include_center, birth, survival }),
+            (Some(radius), Some(_), Some(_)) =>
+                Err(Error::Invalid(format!("radius {} is outside 1..{}", radius, MAX_LTL_RADIUS))),
+            _ => Err(Error::Invalid(format!("rule '{}' needs a radius, a birth and a survival range", s))),
+        }
+    }
+}
+
+impl fmt::Display for LtlRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "R{},C0,M{},S{}..{},B{}..{},NM", self.radius, self.include_center as u8,
+            self.survival.0, self.survival.1, self.birth.0, self.birth.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_block_rules() {
+        let critters = BlockRule::critters();
+        assert_eq!(critters.next(0b0110), 0b0110);
+        assert_eq!(critters.next(0b1000), 0b0111);
+        assert_eq!(critters.next(0b1110), 0b1000);
+        assert_eq!(BlockRule::billiard_ball().next(0b0100), 0b0010);
+        assert!(BlockRule::new(&[0; 15]).is_err());
+        assert!(BlockRule::new(&[16; 16]).is_err());
+    }
+
+    #[test]
+    fn should_parse_hensel_notation() {
+        let rule: Rule = "B2-a/S12".parse().unwrap();
+        assert!(rule.is_isotropic());
+        assert_eq!(rule.to_string(), "B2-a/S12");
+        assert!(rule.is_birth_config(0x81) && !rule.is_birth_config(0x03));
+        assert!(rule.is_survival_config(0x03) && !rule.is_survival_config(0x07));
+
+        let tlife: Rule = "b3/s2-I34Q".parse().unwrap();
+        assert_eq!(tlife.to_string(), "B3/S2-i34q");
+        assert_eq!("B2cekin/S".parse::<Rule>().map(|rule| rule.to_string()), Ok("B2-a/S".to_string()));
+        assert!("B2z/S".parse::<Rule>().is_err());
+        assert!("B2-/S".parse::<Rule>().is_err());
+        assert!("B2a/SV".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn should_round_trip_rule_tables() {
+        let table = [0, 0, 0, 1, 0, 0, 1, 0, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0];
+        let rule = Rule::from_table(&table);
+        assert_eq!(rule, "B36/S23".parse());
+        assert_eq!(rule.map(|rule| rule.table()), Ok(table.to_vec()));
+        assert!(Rule::from_table(&table[..17]).is_err());
+        assert!(Rule::from_table(&[2; 18]).is_err());
+    }
+
+    #[test]
+    fn should_parse_rule_strings() {
+        assert_eq!("B3/S23".parse(), Ok(Rule::conway()));
+        assert_eq!("s23/b3".parse(), Ok(Rule::conway()));
+        assert_eq!("23/3".parse(), Ok(Rule::conway()));
+        assert_eq!("B36/S23".parse(), Ok(Rule::new(&[3, 6], &[2, 3])));
+        assert_eq!("B2/S".parse(), Ok(Rule::new(&[2], &[])));
+        assert!("B9/S23".parse::<Rule>().is_err());
+        assert!("B3S23".parse::<Rule>().is_err());
+        assert!("B3/B23".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn should_parse_neighbourhood_suffix() {
+        let rule = Rule::new(&[2], &[0, 1, 3]).with_neighbourhood(Neighbourhood::VonNeumann);
+        assert_eq!("B2/S013V".parse(), Ok(rule));
+        assert_eq!(rule.to_string(), "B2/S013V");
+        assert!("B2/S015V".parse::<Rule>().is_err());
+        assert_eq!("B2/S34H".parse::<Rule>().map(|rule| rule.neighbourhood()), Ok(Neighbourhood::Hexagonal));
+        assert!("B2/S37H".parse::<Rule>().is_err());
+        assert!(Neighbourhood::Hexagonal.contains(-1, -1) && !Neighbourhood::Hexagonal.contains(-1, 1));
+        assert!(Neighbourhood::VonNeumann.contains(0, 1) && !Neighbourhood::VonNeumann.contains(1, 1));
+    }
+
+    #[test]
+    fn should_parse_generations_rules() {
+        let brain = Rule::generations(&[2], &[], 3);
+        assert_eq!("B2/S/C3".parse(), Ok(brain));
+        assert_eq!("B2/S/3".parse(), Ok(brain));
+        assert_eq!("/2/3".parse(), Ok(brain));
+        assert_eq!(brain.to_string(), "B2/S/C3");
+        assert_eq!("B3/S23/2".parse(), Ok(Rule::conway()));
+        assert!("B2/S/1".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn should_step_generations_states() {
+        let star_wars = Rule::from(RulePreset::StarWars);
+        assert_eq!(star_wars.next_state(0, 2), 1);
+        assert_eq!(star_wars.next_state(1, 3), 1);
+        assert_eq!(star_wars.next_state(1, 2), 2);
+        assert_eq!(star_wars.next_state(2, 2), 3);
+        assert_eq!(star_wars.next_state(3, 2), 0);
+    }
+
+    #[test]
+    fn should_parse_ltl_rules() {
+        let bosco = "R5,C0,M1,S34..58,B34..45,NM".parse();
+        assert_eq!(bosco, Ok(LtlRule::bosco()));
+        assert_eq!(LtlRule::bosco().to_string(), "R5,C0,M1,S34..58,B34..45,NM");
+        assert_eq!("R2,B3,S2..3".parse(), Ok(LtlRule::new(2, false, (3, 3), (2, 3))));
+        assert!("R11,B3,S2..3".parse::<LtlRule>().is_err());
+        assert!("R2,C3,B3,S2..3".parse::<LtlRule>().is_err());
+        assert!("R2,B3".parse::<LtlRule>().is_err());
+    }
+
+    #[test]
+    fn should_display_rule_in_bs_notation() {
+        assert_eq!(Rule::new(&[3, 6, 7, 8], &[3, 4, 6, 7, 8]).to_string(), "B3678/S34678");
+        assert_eq!(Rule::new(&[2], &[]).to_string(), "B2/S");
+    }
+
+    #[test]
+    fn should_map_presets_to_rules() {
+        assert_eq!(Rule::from(RulePreset::Conway).to_string(), "B3/S23");
+        assert_eq!(Rule::from(RulePreset::HighLife).to_string(), "B36/S23");
+        assert_eq!(Rule::from(RulePreset::Maze).to_string(), "B3/S12345");
+    }
+}
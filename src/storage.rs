@@ -0,0 +1,71 @@
+//Saving universes in the browser. Slots hold the share string form of the binary snapshot (see
+//Universe::to_share_string) under a common prefix, so other data on the page is left alone
+
+use wasm_bindgen::prelude::*;
+use web_sys::Storage;
+use universe::Universe;
+use error::Error;
+
+#[cfg(feature = "indexed-db")]
+pub mod indexed_db;
+
+//Put in front of every slot name in localStorage
+const PREFIX: &str = "wasm-game-of-life:";
+
+fn local_storage() -> Result<Storage, JsValue> {
+    web_sys::window()
+        .ok_or_else(|| JsValue::from(Error::Invalid("no window".to_string())))?
+        .local_storage()?
+        .ok_or_else(|| JsValue::from(Error::Invalid("localStorage is not available".to_string())))
+}
+
+//Slot name of a localStorage key, None for keys that aren't ours
+fn slot(key: &str) -> Option<&str> {
+    key.strip_prefix(PREFIX)
+}
+
+#[wasm_bindgen]
+impl Universe {
+    //Store the universe in the slot, replacing what was saved there. Fails when localStorage is
+    //full, which big busy boards can make it
+    pub fn save_to_local_storage(&self, slot: &str) -> Result<(), JsValue> {
+        let text = self.to_share_string()?;
+        local_storage()?.set_item(&format!("{}{}", PREFIX, slot), &text)
+    }
+
+    pub fn load_from_local_storage(slot: &str) -> Result<Universe, JsValue> {
+        let text = local_storage()?.get_item(&format!("{}{}", PREFIX, slot))?
+            .ok_or_else(|| JsValue::from(Error::Invalid(format!("nothing saved in slot {}", slot))))?;
+        Ok(Universe::from_share_string(&text)?)
+    }
+}
+
+//Names of the saved slots in no particular order
+#[wasm_bindgen]
+pub fn list_saved_universes() -> Result<Vec<String>, JsValue> {
+    let storage = local_storage()?;
+    let mut slots = Vec::new();
+    for idx in 0..storage.length()? {
+        if let Some(key) = storage.key(idx)? {
+            slots.extend(slot(&key).map(str::to_string));
+        }
+    }
+    Ok(slots)
+}
+
+//Nothing happens for a slot that doesn't exist
+#[wasm_bindgen]
+pub fn delete_saved_universe(slot: &str) -> Result<(), JsValue> {
+    local_storage()?.remove_item(&format!("{}{}", PREFIX, slot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_list_prefixed_keys() {
+        assert_eq!(slot("wasm-game-of-life:autosave"), Some("autosave"));
+        assert_eq!(slot("theme"), None);
+    }
+}
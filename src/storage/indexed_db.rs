@@ -0,0 +1,103 @@
+//The same slots in IndexedDB, holding the binary snapshot (see Universe::to_bytes) as a
+//Uint8Array. There is no size limit worth worrying about, but every call is asynchronous
+
+use futures::{future, Future};
+use js_sys::{Promise, Uint8Array};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use web_sys::{IdbDatabase, IdbObjectStore, IdbRequest, IdbTransactionMode};
+use universe::Universe;
+use error::Error;
+
+const DATABASE: &str = "wasm-game-of-life";
+const STORE: &str = "universes";
+
+//Promise settled by the success or error event of the request. Only one of the two handlers
+//ever runs, the other one is leaked
+fn settle(request: &IdbRequest) -> Promise {
+    Promise::new(&mut |resolve, reject| {
+        let done = request.clone();
+        let on_success = Closure::once_into_js(move || {
+            let _ = resolve.call1(&JsValue::NULL, &done.result().unwrap_or(JsValue::UNDEFINED));
+        });
+        let failed = request.clone();
+        let on_error = Closure::once_into_js(move || {
+            let error = failed.error().ok().and_then(|error| error).map_or(JsValue::UNDEFINED, JsValue::from);
+            let _ = reject.call1(&JsValue::NULL, &error);
+        });
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    })
+}
+
+//Open the database, creating the store the first time
+fn open() -> Result<Promise, JsValue> {
+    let factory = web_sys::window()
+        .ok_or_else(|| JsValue::from(Error::Invalid("no window".to_string())))?
+        .indexed_db()?
+        .ok_or_else(|| JsValue::from(Error::Invalid("IndexedDB is not available".to_string())))?;
+    let request = factory.open_with_u32(DATABASE, 1)?;
+    let upgrade = request.clone();
+    let on_upgrade = Closure::once_into_js(move || {
+        if let Ok(db) = upgrade.result().and_then(|db| db.dyn_into::<IdbDatabase>()) {
+            let _ = db.create_object_store(STORE);
+        }
+    });
+    request.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+    Ok(settle(&request))
+}
+
+//Result of the request op makes on the store, in a transaction of its own
+fn with_store<F>(mode: IdbTransactionMode, op: F) -> impl Future<Item = JsValue, Error = JsValue>
+    where F: FnOnce(&IdbObjectStore) -> Result<IdbRequest, JsValue> + 'static {
+    future::result(open())
+        .and_then(JsFuture::from)
+        .and_then(move |db| {
+            let db: IdbDatabase = db.dyn_into()?;
+            let store = db.transaction_with_str_and_mode(STORE, mode)?.object_store(STORE)?;
+            Ok(JsFuture::from(settle(&op(&store)?)))
+        })
+        .flatten()
+}
+
+#[wasm_bindgen]
+impl Universe {
+    //Resolves once the snapshot is stored in the slot, replacing what was saved there
+    pub fn save_to_indexed_db(&self, slot: &str) -> Promise {
+        let slot = JsValue::from_str(slot);
+        let bytes = self.to_bytes().map_err(JsValue::from);
+        future_to_promise(future::result(bytes)
+            .and_then(move |bytes| with_store(IdbTransactionMode::Readwrite, move |store| store.put_with_key(&Uint8Array::from(&bytes[..]), &slot)))
+            .map(|_| JsValue::UNDEFINED))
+    }
+
+    //Resolves to the Universe saved in the slot, rejects when there is none
+    pub fn load_from_indexed_db(slot: &str) -> Promise {
+        let name = slot.to_string();
+        let slot = JsValue::from_str(slot);
+        future_to_promise(with_store(IdbTransactionMode::Readonly, move |store| store.get(&slot))
+            .and_then(move |value| {
+                if value.is_undefined() {
+                    return Err(Error::Invalid(format!("nothing saved in slot {}", name)).into());
+                }
+                Universe::from_bytes(&Uint8Array::new(&value).to_vec())
+                    .map(JsValue::from)
+                    .map_err(JsValue::from)
+            }))
+    }
+}
+
+//Resolves to the names of the saved slots in order
+#[wasm_bindgen]
+pub fn list_indexed_db_universes() -> Promise {
+    future_to_promise(with_store(IdbTransactionMode::Readonly, |store| store.get_all_keys()))
+}
+
+//Resolves once the slot is gone, whether or not it existed
+#[wasm_bindgen]
+pub fn delete_indexed_db_universe(slot: &str) -> Promise {
+    let slot = JsValue::from_str(slot);
+    future_to_promise(with_store(IdbTransactionMode::Readwrite, move |store| store.delete(&slot))
+        .map(|_| JsValue::UNDEFINED))
+}
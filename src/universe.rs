@@ -1,131 +1,1452 @@
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use js_sys::{Uint32Array, Uint8Array, WebAssembly};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::ops::Range;
+use rng::{self, Rng};
+use rule::{BlockPreset, BlockRule, CyclicRule, LtlRule, Neighbourhood, Rule, RulePreset, MOORE_OFFSETS};
+use mode::{self, Mode};
+use ant::Ant;
+use utils::vec_bytes;
+use renderer::Palette;
+use error::Error;
+
+mod transform;
+mod draw;
+mod region;
+mod ants;
+mod viewport;
+mod bits;
+mod simd;
+mod lut;
+mod neighbours;
+mod image;
+mod age;
+mod text;
+mod trails;
+mod history;
+mod rle;
+mod snapshot;
+mod config;
+mod events;
+
+pub use self::region::PasteMode;
+pub use self::viewport::Viewport;
+pub use self::age::AgeTracking;
+pub use self::config::UniverseConfig;
+use self::text::Glyphs;
+use self::history::History;
+use self::bits::CellBits;
+use self::lut::WindowTable;
+use self::neighbours::NeighbourTable;
+
+//Largest width or height auto expansion grows a board to
+pub const MAX_AUTO_SIZE: u32 = 4096;
+
+//Most cells a board (or a pattern) may have, 8192x8192. Sizes come from JS and files, and a
+//bigger grid with its double buffer and tables wouldn't fit the wasm memory anyway
+pub const MAX_CELLS: u32 = 1 << 26;
+
+//How many past generations run_until_stable compares against, i.e. the longest period it detects
+const STABLE_HISTORY: usize = 16;
+
+//Collect the indices where two generations of a buffer differ
+fn changed_indices<T: PartialEq>(now: &[T], before: &[T], changed: &mut Vec<u32>) {
+    changed.extend(now.iter().zip(before).enumerate()
+        .filter(|(_, (now, before))| now != before)
+        .map(|(idx, _)| idx as u32));
+}
 
 #[wasm_bindgen]
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Cell {
     Dead = 0,
     Alive = 1,
 }
 
+impl Cell {
+    fn toggle(&mut self) {
+        *self = match *self {
+            Cell::Dead => Cell::Alive,
+            Cell::Alive => Cell::Dead,
+        };
+    }
+}
+
+//Which part of the board stays in place when the universe is resized
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    TopRight,
+    Center,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Anchor {
+    //Offsets (rows, columns) of the old content inside the new board, negative when shrinking
+    fn offset(self, old: (u32, u32), new: (u32, u32)) -> (i64, i64) {
+        let (old_h, old_w) = (i64::from(old.0), i64::from(old.1));
+        let (new_h, new_w) = (i64::from(new.0), i64::from(new.1));
+        match self {
+            Anchor::TopLeft => (0, 0),
+            Anchor::TopRight => (0, new_w - old_w),
+            Anchor::Center => ((new_h - old_h) / 2, (new_w - old_w) / 2),
+            Anchor::BottomLeft => (new_h - old_h, 0),
+            Anchor::BottomRight => (new_h - old_h, new_w - old_w),
+        }
+    }
+}
+
+//What lies beyond the edges of the board: the opposite edge (a torus), dead cells, or the
+//edge cells again as if mirrored, which makes gliders bounce. The twisted surfaces glue edges
+//with a flip: a Klein bottle reverses the columns when wrapping across the top or bottom edge,
+//the cross-surface (real projective plane) also reverses the rows across the left and right ones
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Boundary {
+    Wrap,
+    Dead,
+    Mirror,
+    KleinBottle,
+    CrossSurface,
+}
+
+impl Boundary {
+    //Row or column of the cell seen at position x along an edge of the given size, None when
+    //it's a dead cell past the edge. Along a single line the twisted surfaces just wrap
+    fn resolve(self, x: i64, size: u32) -> Option<usize> {
+        let size = i64::from(size);
+        match self {
+            Boundary::Wrap | Boundary::KleinBottle | Boundary::CrossSurface => Some(x.rem_euclid(size) as usize),
+            Boundary::Dead if x < 0 || x >= size => None,
+            Boundary::Dead => Some(x as usize),
+            Boundary::Mirror => {
+                let folded = x.rem_euclid(2 * size);
+                Some(if folded < size { folded } else { 2 * size - 1 - folded } as usize)
+            }
+        }
+    }
+
+    //Whether wrapping across one edge depends on the position along the other one
+    fn is_twisted(self) -> bool {
+        self == Boundary::KleinBottle || self == Boundary::CrossSurface
+    }
+
+    //Cell seen at (row, column) on a height x width board, None past a dead edge
+    fn resolve_cell(self, row: i64, column: i64, height: u32, width: u32) -> Option<(usize, usize)> {
+        if !self.is_twisted() {
+            return Some((self.resolve(row, height)?, self.resolve(column, width)?));
+        }
+        let (h, w) = (i64::from(height), i64::from(width));
+        let (across_rows, across_columns) = (row.div_euclid(h) % 2 != 0, column.div_euclid(w) % 2 != 0);
+        //Diagonally past a corner of the cross-surface the two flips would land back on the cell
+        //itself, take the opposite corner instead
+        let (flip_columns, flip_rows) = match self {
+            Boundary::CrossSurface if across_rows && across_columns => (false, false),
+            Boundary::CrossSurface => (across_rows, across_columns),
+            _ => (across_rows, false),
+        };
+        let row = if flip_rows { h - 1 - row } else { row };
+        let column = if flip_columns { w - 1 - column } else { column };
+        Some((row.rem_euclid(h) as usize, column.rem_euclid(w) as usize))
+    }
+}
+
 #[allow(dead_code)]
 #[wasm_bindgen]
+#[derive(Debug)]
 pub struct Universe {
     width : u32,
     height: u32,
-    cells: Vec<Cell>,
+    cells: CellBits,
+    next: CellBits,
+    generation: u32,
+    population: u32,
+    changed: Vec<u32>,
+    rule: Rule,
+    mode: Mode,
+    //Per cell state for modes and rules with more than two states (0 dead, 1 alive, 2.. dying
+    //for Generations), empty otherwise
+    states: Vec<u8>,
+    next_states: Vec<u8>,
+    ants: Vec<Ant>,
+    next_ant_id: u32,
+    ants_follow_tick: bool,
+    elementary_rule: u8,
+    ltl_rule: LtlRule,
+    cyclic_rule: CyclicRule,
+    block_rule: BlockRule,
+    boundary: Boundary,
+    viewport: Viewport,
+    //Grow the board when live cells come this close to an edge, 0 for never
+    auto_expand: u32,
+    //Colors of cells painted by ants with more than two colors, empty otherwise
+    ant_colors: Vec<u8>,
+    //Chance that a birth and a survival the rule asks for actually happen, None when both always do
+    noise: Option<(f64, f64)>,
+    rng: Rng,
+    //Which of the two cell buffers is the current one, and how many times they were reallocated
+    front: u8,
+    buffer_version: u32,
+    //Log every cell while ticking, see the debug-trace feature
+    trace: bool,
+    //Whether the next tick has to evaluate every cell, otherwise only the cells changed by the
+    //last one, those written since and their neighbours
+    all_active: bool,
+    written: Vec<u32>,
+    neighbours: NeighbourTable,
+    //Last frame of render_rgba, kept so JS can view it in place
+    frame: Vec<u8>,
+    age_tracking: AgeTracking,
+    //Generations each cell has been in its current state, see AgeTracking. Empty when it's Off
+    ages: Vec<u32>,
+    //Colours set from JS, None for the mode's own
+    custom_palette: Option<Palette>,
+    glyphs: Glyphs,
+    //How much trails fade per generation, 0 for no trails
+    trail_decay: u8,
+    //255 for live cells, fading by trail_decay each generation after they die. Empty without trails
+    trails: Vec<u8>,
+    history: History,
+    //JS callbacks run after each tick, see events
+    on_tick: Option<js_sys::Function>,
+    on_cell_changed: Option<js_sys::Function>,
+}
+
+#[allow(dead_code)]
+#[wasm_bindgen]
+impl Universe {
+    fn get_index(&self, row: u32, column: u32) -> usize {
+        (row * self.width + column) as usize
+    }
+
+    fn wrapped_index(&self, row: u32, column: u32) -> usize {
+        self.get_index(row % self.height, column % self.width)
+    }
+
+    //get_index for coordinates from outside, which may be past the edges
+    fn checked_index(&self, row: u32, column: u32) -> Result<usize, Error> {
+        if row >= self.height || column >= self.width {
+            return Err(Error::OutOfBounds { row, column, width: self.width, height: self.height });
+        }
+        Ok(self.get_index(row, column))
+    }
+
+    //Index of the cell rows and columns away, None past a dead edge
+    fn neighbour_index(&self, row: u32, column: u32, rows: i32, columns: i32) -> Option<usize> {
+        let (r, c) = (i64::from(row) + i64::from(rows), i64::from(column) + i64::from(columns));
+        //Only cells past the edges need the boundary
+        if r >= 0 && c >= 0 && r < i64::from(self.height) && c < i64::from(self.width) {
+            return Some(r as usize * self.width as usize + c as usize);
+        }
+        if rows.abs() <= 1 && columns.abs() <= 1 && !self.boundary.is_twisted() {
+            return self.neighbours.index(row, column, rows, columns);
+        }
+        let (r, c) = self.boundary.resolve_cell(r, c, self.height, self.width)?;
+        Some(r * self.width as usize + c)
+    }
+
+    //Indices of the Moore neighbours in reading order, None for the ones past a dead edge
+    fn moore_neighbours<'a>(&'a self, row: u32, column: u32) -> impl Iterator<Item = Option<usize>> + 'a {
+        MOORE_OFFSETS.iter().map(move |&(rows, columns)| self.neighbour_index(row, column, rows, columns))
+    }
+
+    fn live_neighbour_count(&self, row: u32, column: u32) -> usize {
+        let neighbourhood = self.rule.neighbourhood();
+        //Away from the border rows and columns every neighbour sits at a fixed index offset
+        if row > 0 && column > 0 && row + 1 < self.height && column + 1 < self.width {
+            let idx = self.get_index(row, column) as isize;
+            return MOORE_OFFSETS.iter().zip(self.neighbours.deltas())
+                .filter(|&(&(rows, columns), _)| neighbourhood.contains(rows, columns))
+                .map(|(_, &delta)| self.cells.get((idx + delta) as usize) as usize)
+                .sum();
+        }
+        MOORE_OFFSETS.iter()
+            .filter(|&&(rows, columns)| neighbourhood.contains(rows, columns))
+            .filter_map(|&(rows, columns)| self.neighbour_index(row, column, rows, columns))
+            .map(|idx| self.cells.get(idx) as usize)
+            .sum()
+    }
+
+    //Tick once
+    pub fn tick(&mut self) {
+        profile!("Universe::tick");
+        //Every index the generation functions compute relies on this, check_size keeps it so. Checked
+        //once a tick in release builds too, the row kernels read unchecked with fast-unsafe
+        assert_eq!(self.cells.len(), (self.width * self.height) as usize, "cells don't match the dimensions");
+        let mut next = mem::take(&mut self.next);
+        if self.mode == Mode::Elementary {
+            self.population = self.next_generation_elementary(&mut next);
+        } else if self.mode == Mode::Margolus {
+            self.population = self.next_generation_margolus(&mut next);
+        } else if self.mode == Mode::LargerThanLife {
+            self.population = self.next_generation_ltl(&mut next);
+        } else if self.states.is_empty() && !self.all_active && self.noise.is_none()
+            && 9 * (self.changed.len() + self.written.len()) < self.cells.len() {
+            self.population = self.next_generation_active(&mut next);
+        } else if self.states.is_empty() && self.rows_apply() {
+            self.population = self.next_generation_rows(&mut next);
+        } else if self.states.is_empty() {
+            self.population = self.next_generation(&mut next);
+        } else {
+            let mut next_states = mem::take(&mut self.next_states);
+            self.population = self.next_generation_states(&mut next, &mut next_states);
+            mem::swap(&mut self.states, &mut next_states);
+            self.next_states = next_states;
+        }
+        if let Some((p_birth, p_survive)) = self.noise {
+            self.population -= self.apply_noise(&mut next, p_birth, p_survive);
+        }
+        mem::swap(&mut self.cells, &mut next);
+        self.next = next;
+        self.front ^= 1;
+        self.generation += 1;
+        self.all_active = false;
+        self.written.clear();
+
+        self.changed.clear();
+        if self.states.is_empty() {
+            self.cells.changed(&self.next, &mut self.changed);
+        } else {
+            changed_indices(&self.states, &self.next_states, &mut self.changed);
+        }
+        self.update_ages();
+        self.update_trails();
+        if self.history.capacity() > 0 {
+            let (births, deaths) = self.cells.transitions(&self.next);
+            self.history.push(self.population, births, deaths);
+        }
+
+        if self.ants_follow_tick {
+            self.step_ants(1);
+        }
+        let mut grew = false;
+        if self.auto_expand > 0 {
+            let margin = self.auto_expand;
+            grew = self.expand_to_fit(margin);
+        }
+        self.emit_tick(grew);
+    }
+
+    //Randomly call off births and survivals of the generation just computed in next (states are
+    //already swapped in), returns how many live cells that removed
+    fn apply_noise(&mut self, next: &mut CellBits, p_birth: f64, p_survive: f64) -> u32 {
+        if self.mode == Mode::Elementary || self.mode == Mode::Wireworld || self.mode == Mode::Margolus {
+            return 0;
+        }
+        let dying = if self.mode == Mode::Life && self.rule.states() > 2 { 2 } else { 0 };
+        let mut removed = 0;
+        next.retain_alive(|idx| {
+            let (chance, state) = match self.cells.get(idx) {
+                Cell::Dead => (p_birth, 0),
+                Cell::Alive => (p_survive, dying),
+            };
+            let keep = self.rng.next_f64() < chance;
+            if !keep {
+                if !self.states.is_empty() {
+                    self.states[idx] = state;
+                }
+                removed += 1;
+            }
+            keep
+        });
+        removed
+    }
+
+    //Tick n generations without leaving wasm, the two cell buffers are swapped between generations
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+
+    //Tick until the cells stop changing or repeat one of the recent generations, at most max_gens times.
+    //Returns the number of generations ticked
+    pub fn run_until_stable(&mut self, max_gens: u32) -> u32 {
+        let mut history: VecDeque<Vec<u8>> = VecDeque::with_capacity(STABLE_HISTORY);
+        for gen in 1..=max_gens {
+            let mut snapshot = if history.len() == STABLE_HISTORY {
+                history.pop_front().unwrap()
+            } else {
+                Vec::with_capacity(self.cells.len())
+            };
+            snapshot.clear();
+            snapshot.extend(self.state_bytes());
+            history.push_back(snapshot);
+
+            self.tick();
+            if history.iter().any(|past| past.iter().cloned().eq(self.state_bytes())) {
+                return gen;
+            }
+        }
+        max_gens
+    }
+
+    //Fill next with the following generation and return its population
+    fn next_generation(&self, next: &mut CellBits) -> u32 {
+        next.clear();
+        next.extend(iproduct!(0..self.height, 0..self.width).map(|(row, col)| self.next_cell(row, col)));
+        next.count_alive()
+    }
+
+    //Same as next_generation, but only cells that changed in the last generation or were written
+    //since, and their neighbours, can change. Every other cell is copied over
+    fn next_generation_active(&self, next: &mut CellBits) -> u32 {
+        let mut active = Vec::with_capacity(9 * (self.changed.len() + self.written.len()));
+        for &idx in self.changed.iter().chain(&self.written) {
+            let (row, col) = (idx / self.width, idx % self.width);
+            active.push(idx as usize);
+            active.extend(self.moore_neighbours(row, col).flatten());
+        }
+        active.sort_unstable();
+        active.dedup();
+        next.clone_from(&self.cells);
+        for idx in active {
+            let (row, col) = (idx as u32 / self.width, idx as u32 % self.width);
+            next.set(idx, self.next_cell(row, col));
+        }
+        next.count_alive()
+    }
+
+    //Whether next_generation_rows can run the current boundary
+    fn rows_apply(&self) -> bool {
+        self.mode == Mode::Life && !self.boundary.is_twisted() && !self.trace
+    }
+
+    //Same as next_generation a whole row at a time
+    #[cfg(not(feature = "threads"))]
+    fn next_generation_rows(&self, next: &mut CellBits) -> u32 {
+        next.clear();
+        self.next_rows(0..self.height as usize, |out| {
+            next.extend(out.iter().map(|&cell| if cell == 1 { Cell::Alive } else { Cell::Dead }));
+        });
+        next.count_alive()
+    }
+
+    //Same as next_generation, bands of rows are computed in parallel on the rayon pool
+    #[cfg(feature = "threads")]
+    fn next_generation_rows(&self, next: &mut CellBits) -> u32 {
+        use rayon::prelude::*;
+        let (width, height) = (self.width as usize, self.height as usize);
+        let band = height.div_ceil(rayon::current_num_threads()).max(1);
+        let mut bytes = vec![0u8; width * height];
+        next.clear();
+        if bytes.is_empty() {
+            return 0;
+        }
+        bytes.par_chunks_mut(band * width).enumerate().for_each(|(idx, chunk)| {
+            let first = idx * band;
+            let last = first + chunk.len() / width;
+            let mut rows = chunk.chunks_mut(width);
+            self.next_rows(first..last, |out| {
+                if let Some(row) = rows.next() {
+                    row.copy_from_slice(out);
+                }
+            });
+        });
+        next.extend(bytes.iter().map(|&cell| if cell == 1 { Cell::Alive } else { Cell::Dead }));
+        next.count_alive()
+    }
+
+    //Next generation of the given rows, handed to emit one byte per cell and row by row. Each row
+    //is looked up 3x3 window by window, totalistic Moore rules do the most they can with simd first
+    fn next_rows<F: FnMut(&[u8])>(&self, rows: Range<usize>, mut emit: F) {
+        let width = self.width as usize;
+        let table = WindowTable::new(&self.rule);
+        let totalistic = self.rule.neighbourhood() == Neighbourhood::Moore && !self.rule.is_isotropic();
+        let (mut birth, mut survival) = ([0u8; 16], [0u8; 16]);
+        for count in 0..9 {
+            birth[count] = self.rule.is_birth(count) as u8;
+            survival[count] = self.rule.is_survival(count) as u8;
+        }
+        let unpack = |row: i64, line: &mut Vec<u8>| {
+            line.clear();
+            match self.neighbours.row(row) {
+                Some(row) => line.extend(self.neighbours.columns().iter().map(|col| col.map_or(0, |col| self.cells.get(row * width + col) as u8))),
+                None => line.resize(width + 2, 0),
+            }
+        };
+        let (mut above, mut line, mut below) = (Vec::new(), Vec::new(), Vec::new());
+        unpack(rows.start as i64 - 1, &mut above);
+        unpack(rows.start as i64, &mut line);
+        let mut out = vec![0u8; width];
+        for row in rows {
+            unpack(row as i64 + 1, &mut below);
+            let start = if totalistic { simd::next_row(&above, &line, &below, &birth, &survival, &mut out) } else { 0 };
+            table.next_row(&above, &line, &below, start, &mut out);
+            emit(&out);
+            mem::swap(&mut above, &mut line);
+            mem::swap(&mut line, &mut below);
+        }
+    }
+
+    fn next_cell(&self, row: u32, col: u32) -> Cell {
+        let idx = self.get_index(row, col);
+        let cell = self.cells.get(idx);
+        let nbr_cnt = self.live_neighbour_count(row, col);
+
+        trace!(self.trace, "Cell [{},{}] is initially {:?} and has {} live neighbors",
+            row, col, cell, nbr_cnt);
+
+        let newstate = if self.rule.is_isotropic() {
+            self.rule.next_config(cell, self.neighbour_config(row, col))
+        } else {
+            self.rule.next(cell, nbr_cnt)
+        };
+
+        trace!(self.trace, " it becomes {:?}", newstate);
+
+        newstate
+    }
+
+    //Same as next_generation for multi-state cells
+    fn next_generation_states(&self, next: &mut CellBits, next_states: &mut Vec<u8>) -> u32 {
+        next.clear();
+        next_states.clear();
+        for (row, col) in iproduct!(0..self.height, 0..self.width) {
+            let idx = self.get_index(row, col);
+            let state = match self.mode {
+                Mode::Wireworld => mode::wireworld_next(self.states[idx], self.neighbour_states(row, col, mode::WIRE_HEAD)),
+                Mode::Immigration | Mode::QuadLife => mode::colored_next(&self.rule, self.states[idx], &self.neighbour_colors(row, col)),
+                Mode::Cyclic => {
+                    let state = self.states[idx];
+                    self.cyclic_rule.next(state, self.neighbour_states(row, col, self.cyclic_rule.successor(state)))
+                }
+                _ if self.rule.is_isotropic() => self.rule.next_state_config(self.states[idx], self.neighbour_config(row, col)),
+                _ => self.rule.next_state(self.states[idx], self.live_neighbour_count(row, col)),
+            };
+            next_states.push(state);
+            next.push(self.cell_for_state(state));
+        }
+        next.count_alive()
+    }
+
+    //Scroll every row up by one and compute the new bottom row from the old one
+    fn next_generation_elementary(&self, next: &mut CellBits) -> u32 {
+        let width = self.width as usize;
+        let bottom = self.cells.len() - width;
+        next.clear();
+        next.extend((width..self.cells.len()).map(|idx| self.cells.get(idx)));
+        next.extend((0..width as i64).map(|col| {
+            let alive = |c: i64| self.boundary.resolve(c, self.width).is_some_and(|c| self.cells.get(bottom + c) == Cell::Alive);
+            let born = mode::elementary_next(self.elementary_rule, alive(col - 1), alive(col), alive(col + 1));
+            if born { Cell::Alive } else { Cell::Dead }
+        }));
+        next.count_alive()
+    }
+
+    //Replace every 2x2 block of the partition for this generation: blocks start on even rows and
+    //columns in even generations and on odd ones (wrapping around on a torus) in odd generations.
+    //The cells of incomplete blocks on the edges, with an odd size or without wrapping, are left
+    //as they are
+    fn next_generation_margolus(&self, next: &mut CellBits) -> u32 {
+        let offset = self.generation % 2;
+        next.clone_from(&self.cells);
+        let wrap = if self.boundary == Boundary::Wrap { offset } else { 0 };
+        let starts = |size: u32| (offset..(size + wrap).saturating_sub(1)).step_by(2);
+        for (row, col) in iproduct!(starts(self.height), starts(self.width)) {
+            let idx = [
+                self.get_index(row, col),
+                self.get_index(row, (col + 1) % self.width),
+                self.get_index((row + 1) % self.height, col),
+                self.get_index((row + 1) % self.height, (col + 1) % self.width),
+            ];
+            let block = idx.iter().fold(0, |block, &i| block << 1 | self.cells.get(i) as u8);
+            let block = self.block_rule.next(block);
+            for (bit, &i) in idx.iter().enumerate() {
+                next.set(i, if block & 8 >> bit != 0 { Cell::Alive } else { Cell::Dead });
+            }
+        }
+        next.count_alive()
+    }
+
+    fn next_generation_ltl(&self, next: &mut CellBits) -> u32 {
+        let counts = self.box_counts(self.ltl_rule.radius());
+        next.clear();
+        next.extend(self.cells.iter().zip(&counts).map(|(cell, &count)| {
+            let neighbours = if self.ltl_rule.include_center() { count } else { count - cell as u32 };
+            self.ltl_rule.next(cell, neighbours)
+        }));
+        next.count_alive()
+    }
+
+    //Alive cells in the (2 * radius + 1)^2 box around every cell, center included, wrapping around
+    //the edges. Sliding window sums along rows then along columns keep it linear in the board size
+    //whatever the radius
+    fn box_counts(&self, radius: u32) -> Vec<u32> {
+        //Twisted edges don't split into rows and columns, count every box cell by cell
+        if self.boundary.is_twisted() {
+            let r = radius as i32;
+            return iproduct!(0..self.height, 0..self.width)
+                .map(|(row, col)| iproduct!(-r..=r, -r..=r)
+                    .filter_map(|(rows, columns)| self.neighbour_index(row, col, rows, columns))
+                    .map(|idx| self.cells.get(idx) as u32)
+                    .sum())
+                .collect();
+        }
+        let (width, height, r) = (self.width as usize, self.height as usize, i64::from(radius));
+        let boundary = self.boundary;
+        let mut rows = vec![0u32; width * height];
+        for row in 0..height {
+            let at = |col: i64| boundary.resolve(col, self.width).map_or(0, |col| self.cells.get(row * width + col) as u32);
+            let mut sum: u32 = (-r..=r).map(at).sum();
+            for col in 0..width as i64 {
+                rows[row * width + col as usize] = sum;
+                sum += at(col + r + 1);
+                sum -= at(col - r);
+            }
+        }
+        let mut counts = vec![0u32; width * height];
+        for col in 0..width {
+            let at = |row: i64| boundary.resolve(row, self.height).map_or(0, |row| rows[row * width + col]);
+            let mut sum: u32 = (-r..=r).map(at).sum();
+            for row in 0..height as i64 {
+                counts[row as usize * width + col] = sum;
+                sum += at(row + r + 1);
+                sum -= at(row - r);
+            }
+        }
+        counts
+    }
+
+    //Number of neighbours in the given state
+    fn neighbour_states(&self, row: u32, column: u32, state: u8) -> usize {
+        self.moore_neighbours(row, column)
+            .filter(|idx| idx.is_some_and(|idx| self.states[idx] == state))
+            .count()
+    }
+
+    //Byte of the eight Moore neighbours read row by row, north-west in the top bit, as used by
+    //Hensel notation rules
+    fn neighbour_config(&self, row: u32, column: u32) -> u8 {
+        self.moore_neighbours(row, column)
+            .fold(0, |config, idx| config << 1 | idx.map_or(0, |idx| self.cells.get(idx) as u8))
+    }
+
+    //Live neighbours of each colour (states 1 to MAX_COLORS) in the rule's neighbourhood
+    fn neighbour_colors(&self, row: u32, column: u32) -> [usize; mode::MAX_COLORS] {
+        let mut colors = [0; mode::MAX_COLORS];
+        let neighbourhood = self.rule.neighbourhood();
+        let neighbours = MOORE_OFFSETS.iter()
+            .filter(|&&(rows, columns)| neighbourhood.contains(rows, columns))
+            .filter_map(|&(rows, columns)| self.neighbour_index(row, column, rows, columns));
+        for idx in neighbours {
+            match self.states[idx] {
+                0 => {}
+                color => colors[color as usize - 1] += 1,
+            }
+        }
+        colors
+    }
+
+    //Random soup of the given size, new Universe(width, height) from JS. Throws for a side of 0
+    //or more cells than wasm can index
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: u32, height: u32) -> Result<Universe, Error> {
+        Universe::check_size(width, height)?;
+        Ok(Universe::soup(width, height))
+    }
+
+    //Universe of the given size with every cell dead, throwing for sizes new turns down
+    #[wasm_bindgen(js_name = empty)]
+    pub fn empty_js(width: u32, height: u32) -> Result<Universe, Error> {
+        Universe::check_size(width, height)?;
+        Ok(Universe::empty(width, height))
+    }
+
+    //Fill the universe with random noise, each cell is alive with the given probability (cyclic
+    //automata pick any state for every cell instead). The same seed always produces the same soup
+    pub fn randomize(&mut self, density: f64, seed: u64) {
+        let mut rng = Rng::new(seed);
+        let len = self.cells.len();
+        self.cells.clear();
+        self.cells.extend((0..len).map(|_| if rng.next_f64() < density {
+            Cell::Alive
+        } else {
+            Cell::Dead
+        }));
+        self.generation = 0;
+        self.population = self.cells.count_alive();
+        self.reset_states();
+        //Wireworld soups need some electrons on the wires to do anything
+        if self.mode == Mode::Wireworld {
+            for state in self.states.iter_mut().filter(|state| **state != mode::WIRE_EMPTY) {
+                *state = mode::WIRE_CONDUCTOR + (rng.next_u64() % 3) as u8;
+            }
+        }
+        //Cyclic soups use every state, dead cells included
+        if self.mode == Mode::Cyclic {
+            let states = u64::from(self.cyclic_rule.states());
+            for idx in 0..self.cells.len() {
+                let state = (rng.next_u64() % states) as u8;
+                self.write_state(idx, state);
+            }
+        }
+        //Coloured soups start with every colour mixed in
+        let colors = self.mode.colors() as u64;
+        if colors > 0 {
+            for state in self.states.iter_mut().filter(|state| **state != 0) {
+                *state = 1 + (rng.next_u64() % colors) as u8;
+            }
+        }
+    }
+
+    pub fn render(&self) -> String {
+        profile!("Universe::render");
+        self.to_string()
+    }
+
+    //Read as properties from JS, universe.width and not universe.width()
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    //64-bit FNV-1a over the dimensions and cells, equal universes always hash the same
+    pub fn state_hash(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+        self.width.to_le_bytes().iter()
+            .chain(&self.height.to_le_bytes())
+            .cloned()
+            .chain(self.state_bytes())
+            .fold(FNV_OFFSET, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME))
+    }
+
+    //Switch to another Life-like rule given in B/S notation, e.g. "B36/S23" for HighLife
+    #[wasm_bindgen(js_name = set_rule)]
+    pub fn set_rule_js(&mut self, rule: &str) -> Result<(), Error> {
+        let rule = rule.parse()?;
+        self.set_rule(rule);
+        Ok(())
+    }
+
+    //Install a 2x9 totalistic table (see Rule::from_table), e.g. straight from a Uint8Array
+    pub fn set_rule_table(&mut self, table: &[u8]) -> Result<(), Error> {
+        let rule = Rule::from_table(table)?;
+        self.set_rule(rule);
+        Ok(())
+    }
+
+    pub fn rule_table(&self) -> Vec<u8> {
+        self.rule.table()
+    }
+
+    pub fn set_preset(&mut self, preset: RulePreset) {
+        self.set_rule(Rule::from(preset));
+    }
+
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.rule.neighbourhood()
+    }
+
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+        self.neighbours = NeighbourTable::new(self.width, self.height, boundary);
+        self.all_active = true;
+    }
+
+    //Count neighbours over another neighbourhood, keeping the birth and survival counts
+    pub fn set_neighbourhood(&mut self, neighbourhood: Neighbourhood) {
+        let rule = self.rule.with_neighbourhood(neighbourhood);
+        self.set_rule(rule);
+    }
+
+    //Packed [x0, y0, x1, y1, ...] canvas centers of every cell drawn as a pointy-top hexagon with
+    //the given circumradius, in the same order as cells(). Matches the Hexagonal neighbourhood
+    pub fn hex_centers(&self, size: f64) -> Vec<f64> {
+        let step = size * 3f64.sqrt();
+        let last_row = f64::from(self.height) - 1.0;
+        iproduct!(0..self.height, 0..self.width)
+            .flat_map(|(row, col)| {
+                let (row, col) = (f64::from(row), f64::from(col));
+                vec![step * (col + (last_row - row) / 2.0 + 0.5), size * (1.0 + 1.5 * row)]
+            })
+            .collect()
+    }
+
+    //Current rule in B/S notation
+    #[wasm_bindgen(js_name = rule)]
+    pub fn rule_string(&self) -> String {
+        self.rule.to_string()
+    }
+
+    //Number of alive cells, kept up to date by tick and the cell setters
+    pub fn population(&self) -> u32 {
+        self.population
+    }
+
+    //Bit-packed cells, 1 for alive: cell row * width + column is bit idx % 64 of u64 word idx / 64.
+    //Words are little endian, so on the JS side a Uint32Array over cell_words() * 2 entries holds
+    //it as bit idx % 32 of entry idx / 32
+    pub fn cells(&self) -> *const u64 {
+        self.cells.words().as_ptr()
+    }
+
+    //The cells() buffer as a Uint8Array straight over wasm memory, no pointer arithmetic or
+    //copying needed: cell idx is bit idx % 8 of byte idx / 8. The view follows the front buffer
+    //it was made on, so take a fresh one after every tick (or one per front_buffer() value). It
+    //goes stale, empty and detached, when the buffers are reallocated or wasm memory grows, check
+    //buffer_version() before reading an old one
+    pub fn cells_view(&self) -> Uint8Array {
+        let memory: WebAssembly::Memory = wasm_bindgen::memory().unchecked_into();
+        let words = self.cells.words();
+        Uint8Array::new_with_byte_offset_and_length(&memory.buffer(), words.as_ptr() as u32, mem::size_of_val(words) as u32)
+    }
+
+    //Bytes taken by the universe: both cell buffers, the state and change lists, ants and their
+    //colors. Allocator bookkeeping isn't counted
+    pub fn memory_usage(&self) -> usize {
+        mem::size_of::<Universe>() + self.cells.memory_usage() + self.next.memory_usage()
+            + vec_bytes(&self.states) + vec_bytes(&self.next_states) + vec_bytes(&self.changed)
+            + vec_bytes(&self.written) + vec_bytes(&self.ants) + vec_bytes(&self.ant_colors)
+            + vec_bytes(&self.frame) + vec_bytes(&self.ages) + vec_bytes(&self.trails)
+            + self.history.memory_usage()
+    }
+
+    //Which of the two persistent cell buffers (0 or 1) cells() and states() point into, it flips
+    //on every tick. A view made on each buffer stays valid until buffer_version changes
+    pub fn front_buffer(&self) -> u8 {
+        self.front
+    }
+
+    //Bumped whenever the cell buffers are reallocated (resizing, loading cells, switching
+    //between two-state and multi-state rules), JS must then recreate its views
+    pub fn buffer_version(&self) -> u32 {
+        self.buffer_version
+    }
+
+    //Length of the cells() buffer in 64-bit words
+    pub fn cell_words(&self) -> u32 {
+        self.cells.words().len() as u32
+    }
+
+    //Bits per word of the cells() buffer
+    pub fn cell_word_bits(&self) -> u32 {
+        bits::WORD_BITS as u32
+    }
+
+    //One byte per cell (0 dead, 1 alive, 2.. dying) while the rule has more than two states
+    pub fn states(&self) -> *const u8 {
+        self.states.as_ptr()
+    }
+
+    //Number of cell states of the current mode and rule, 2 for plain Life-like rules
+    pub fn state_count(&self) -> u8 {
+        self.mode.state_count(&self.rule, &self.cyclic_rule)
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    //Wolfram rule number used in Elementary mode, 30 by default
+    pub fn elementary_rule(&self) -> u8 {
+        self.elementary_rule
+    }
+
+    pub fn set_elementary_rule(&mut self, rule: u8) {
+        self.elementary_rule = rule;
+    }
+
+    //Switch to Larger than Life with a rule in Golly's notation, e.g. "R5,C0,M1,S34..58,B34..45,NM"
+    #[wasm_bindgen(js_name = set_ltl_rule)]
+    pub fn set_ltl_rule_js(&mut self, rule: &str) -> Result<(), Error> {
+        let rule = rule.parse()?;
+        self.set_ltl_rule(rule);
+        Ok(())
+    }
+
+    //Switch to a cyclic automaton with the given number of states (at least 3) where a cell gets
+    //eaten once threshold (1 to 8) of its neighbours are in the next state
+    pub fn set_cyclic(&mut self, states: u8, threshold: u8) {
+        self.set_cyclic_rule(CyclicRule::new(states, threshold));
+    }
+
+    //Switch to the Margolus block automaton with one of the well known block rules
+    pub fn set_block_preset(&mut self, preset: BlockPreset) {
+        self.set_block_rule(BlockRule::from(preset));
+    }
+
+    //Switch to the Margolus block automaton with a 16 entry table, see BlockRule
+    #[wasm_bindgen(js_name = set_block_rule)]
+    pub fn set_block_rule_js(&mut self, table: &[u8]) -> Result<(), Error> {
+        let rule = BlockRule::new(table)?;
+        self.set_block_rule(rule);
+        Ok(())
+    }
+
+    pub fn block_rule_table(&self) -> Vec<u8> {
+        self.block_rule.table().to_vec()
+    }
+
+    //0xRRGGBB colour for each state of the current mode, index it with the states() buffer. A
+    //palette set with set_palette replaces the first two
+    pub fn palette(&self) -> Vec<u32> {
+        let count = self.state_count();
+        (0..count).map(|state| match (state, &self.custom_palette) {
+            (0, Some(palette)) => palette.dead(),
+            (1, Some(palette)) => palette.alive(),
+            _ => mode::state_color(self.mode, state, count),
+        }).collect()
+    }
+
+    #[wasm_bindgen(js_name = ltl_rule)]
+    pub fn ltl_rule_string(&self) -> String {
+        self.ltl_rule.to_string()
+    }
+
+    //Switch automaton, cells keep whether they are alive but lose any other state
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+        self.reset_states();
+    }
+
+    //Make births and survivals only happen with the given probabilities (clamped to [0, 1]),
+    //set_noise(1, 1) turns the noise back off
+    pub fn set_noise(&mut self, p_birth: f64, p_survive: f64) {
+        let (p_birth, p_survive) = (p_birth.clamp(0.0, 1.0), p_survive.clamp(0.0, 1.0));
+        self.noise = if p_birth == 1.0 && p_survive == 1.0 { None } else { Some((p_birth, p_survive)) };
+    }
+
+    //Turn per cell logging in tick on or off. Builds without the debug-trace feature never log
+    pub fn set_trace(&mut self, on: bool) {
+        self.trace = on;
+    }
+
+    //Reseed the generator behind set_noise, the same seed replays the same run
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    pub fn cell_state(&self, row: u32, column: u32) -> u8 {
+        let idx = self.wrapped_index(row, column);
+        if self.states.is_empty() { self.cells.get(idx) as u8 } else { self.states[idx] }
+    }
+
+    //Set the raw state of a cell, states past the mode's last one are clamped to it
+    pub fn set_cell_state(&mut self, row: u32, column: u32, state: u8) {
+        let idx = self.wrapped_index(row, column);
+        self.write_state(idx, state);
+    }
+
+    //Coordinates of alive cells packed as [row0, col0, row1, col1, ...], a Uint32Array on the JS side
+    pub fn live_cells(&self) -> Vec<u32> {
+        let mut coords = Vec::with_capacity(2 * self.population as usize);
+        for idx in self.cells.ones() {
+            coords.push(idx as u32 / self.width);
+            coords.push(idx as u32 % self.width);
+        }
+        coords
+    }
+
+    //Indices of the cells flipped by the last tick, so renderers can redraw only those
+    pub fn changed_cells(&self) -> Vec<u32> {
+        self.changed.clone()
+    }
+
+    //Same indices without copying them out: a Uint32Array of changed_len() entries at this
+    //pointer. The buffer is refilled by every tick and may move when it grows, so read both again
+    //after ticking
+    pub fn changed_ptr(&self) -> *const u32 {
+        self.changed.as_ptr()
+    }
+
+    pub fn changed_len(&self) -> u32 {
+        self.changed.len() as u32
+    }
+
+    //[min_row, min_col, max_row, max_col] of the alive cells, undefined when everything is dead
+    #[wasm_bindgen(js_name = bounding_box)]
+    pub fn bounding_box_js(&self) -> Option<Vec<u32>> {
+        self.bounding_box().map(|(r0, c0, r1, c1)| vec![r0, c0, r1, c1])
+    }
+
+    //Shrink the universe to the live bounding box plus margin, returns the removed [rows, columns] offset
+    #[wasm_bindgen(js_name = crop_to_content)]
+    pub fn crop_to_content_js(&mut self, margin: u32) -> Vec<u32> {
+        let (rows, cols) = self.crop_to_content(margin);
+        vec![rows, cols]
+    }
+
+    //Reset all cells to dead after this set. The sizes check_size turns down leave the board as it is
+    pub fn set_width(&mut self, width: u32) -> Result<(), Error> {
+        Universe::check_size(width, self.height)?;
+        self.width = width;
+        self.cells.resize((width * self.height) as usize, Cell::Dead);
+        self.clear();
+        Ok(())
+    }
+
+    //Reset all cells to dead after this reset
+    pub fn set_height(&mut self, height: u32) -> Result<(), Error> {
+        Universe::check_size(self.width, height)?;
+        self.height = height;
+        self.cells.resize((self.width * height) as usize, Cell::Dead);
+        self.clear();
+        Ok(())
+    }
+
+    //Change the dimensions keeping the overlapping top-left region alive
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), Error> {
+        self.resize_anchored(width, height, Anchor::TopLeft)
+    }
+
+    //Change the dimensions keeping the cells that still fit when the old board is placed at anchor
+    pub fn resize_anchored(&mut self, width: u32, height: u32, anchor: Anchor) -> Result<(), Error> {
+        Universe::check_size(width, height)?;
+        let (dr, dc) = anchor.offset((self.height, self.width), (height, width));
+        let (old_height, old_width) = (i64::from(self.height), i64::from(self.width));
+        self.rebuild(width, height, |row, col| {
+            let (r, c) = (i64::from(row) - dr, i64::from(col) - dc);
+            if r < 0 || c < 0 || r >= old_height || c >= old_width {
+                None
+            } else {
+                Some((r as u32, c as u32))
+            }
+        });
+        Ok(())
+    }
+
+    //Replace the whole grid from a Uint8Array holding one byte per cell, non-zero bytes are alive
+    #[wasm_bindgen(js_name = set_all_cells)]
+    pub fn set_all_cells_js(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.set_all_cells(bytes)
+    }
+
+    //Grow the board whenever live cells get within margin cells of an edge (0 turns it off).
+    //Each crowded side gains half the board's size (at least margin) up to MAX_AUTO_SIZE, so
+    //check width() and height() after ticking
+    pub fn set_auto_expand(&mut self, margin: u32) {
+        self.auto_expand = margin;
+    }
+
+    pub fn auto_expand(&self) -> u32 {
+        self.auto_expand
+    }
+
+    //Kill every cell and restart counting generations, dimensions are kept
+    pub fn clear(&mut self) {
+        self.cells.fill(Cell::Dead);
+        self.generation = 0;
+        self.population = 0;
+        self.changed.clear();
+        self.reset_states();
+    }
+
+    //Bring packed [row0, col0, row1, col1, ...] cells to life, throwing for cells past the edges
+    //(set_cell wraps them instead) or a trailing lone row
+    #[wasm_bindgen(js_name = set_cells)]
+    pub fn set_cells_js(&mut self, cells: &[u32]) -> Result<(), Error> {
+        if !cells.len().is_multiple_of(2) {
+            return Err(Error::Invalid(format!("{} numbers aren't row and column pairs", cells.len())));
+        }
+        let cells: Vec<(u32, u32)> = cells.chunks(2).map(|pair| (pair[0], pair[1])).collect();
+        self.set_cells(&cells)
+    }
+
+    //Flip a single cell, coordinates wrap around the edges the same way tick does
+    pub fn toggle_cell(&mut self, row: u32, column: u32) {
+        let idx = self.wrapped_index(row, column);
+        let mut state = self.cells.get(idx);
+        state.toggle();
+        self.write_cell(idx, state);
+    }
+
+    pub fn get_cell(&self, row: u32, column: u32) -> Cell {
+        self.cells.get(self.wrapped_index(row, column))
+    }
+
+    pub fn set_cell(&mut self, row: u32, column: u32, state: Cell) {
+        let idx = self.wrapped_index(row, column);
+        self.write_cell(idx, state);
+    }
+}
+
+impl Default for Universe {
+    fn default() -> Universe {
+        Universe::soup(64, 64)
+    }
 }
 
-#[allow(dead_code)]
-#[wasm_bindgen]
-impl Universe {
-    fn get_index(&self, row: u32, column: u32) -> usize {
-        (row * self.width + column) as usize
+//No binding in those implementation functions
+impl Universe {
+    //Error for sizes a board can't have: an empty side, which leaves nothing to wrap around, or
+    //more than MAX_CELLS cells
+    pub fn check_size(width: u32, height: u32) -> Result<(), Error> {
+        match width.checked_mul(height) {
+            Some(0) => Err(Error::Invalid(format!("a {}x{} universe has no cells", width, height))),
+            Some(cells) if cells <= MAX_CELLS => Ok(()),
+            _ => Err(Error::Invalid(format!("a {}x{} universe has too many cells", width, height))),
+        }
+    }
+
+    //Panics for a size check_size turns down, JS gets the checked empty_js. Boards always have
+    //cells, so nothing else has to handle an empty side
+    pub fn empty(width: u32, height: u32) -> Universe {
+        Universe::check_size(width, height).unwrap_or_else(|error| panic!("{}", error));
+        Universe::with_cells(width, height, CellBits::filled((width * height) as usize, Cell::Dead))
+    }
+
+    fn soup(width: u32, height: u32) -> Universe {
+        super::utils::set_panic_hook();
+
+        let mut universe = Universe::empty(width, height);
+        universe.randomize(0.5, rng::random_seed());
+        universe
+    }
+
+    fn with_cells<C: Into<CellBits>>(width: u32, height: u32, cells: C) -> Universe {
+        Universe::check_size(width, height).unwrap_or_else(|error| panic!("{}", error));
+        let cells: CellBits = cells.into();
+        let population = cells.count_alive();
+        Universe {width, height, next: cells.clone(), cells, generation: 0, population, changed: Vec::new(),
+            rule: Rule::default(), mode: Mode::Life, states: Vec::new(), next_states: Vec::new(),
+            ants: Vec::new(), next_ant_id: 0, ants_follow_tick: false, ant_colors: Vec::new(),
+            elementary_rule: 30, ltl_rule: LtlRule::default(),
+            cyclic_rule: CyclicRule::default(), block_rule: BlockRule::default(),
+            boundary: Boundary::Wrap, viewport: Viewport::new(0, 0, height, width, 1),
+            auto_expand: 0, noise: None, rng: Rng::new(0), front: 0, buffer_version: 0,
+            trace: false, all_active: true, written: Vec::new(),
+            neighbours: NeighbourTable::new(width, height, Boundary::Wrap), frame: Vec::new(),
+            age_tracking: AgeTracking::Off, ages: Vec::new(), custom_palette: None,
+            glyphs: Glyphs::default(), trail_decay: 0, trails: Vec::new(),
+            history: History::new(0), on_tick: None, on_cell_changed: None}
+    }
+
+    //Take over the mode and rules of other, cells are left alone
+    fn copy_settings(&mut self, other: &Universe) {
+        self.rule = other.rule;
+        self.mode = other.mode;
+        self.elementary_rule = other.elementary_rule;
+        self.ltl_rule = other.ltl_rule;
+        self.cyclic_rule = other.cyclic_rule;
+        self.block_rule = other.block_rule;
+        self.set_boundary(other.boundary);
+        self.auto_expand = other.auto_expand;
+        self.noise = other.noise;
+        self.rng = other.rng.clone();
+        self.set_age_tracking(other.age_tracking);
+        self.custom_palette = other.custom_palette.clone();
+        self.glyphs = other.glyphs.clone();
+        self.set_trail_decay(other.trail_decay);
+        self.history = History::new(other.history.capacity());
+    }
+
+    fn write_cell(&mut self, idx: usize, state: Cell) {
+        if !self.all_active {
+            self.written.push(idx as u32);
+            //Past that many a full tick is as cheap
+            if 9 * self.written.len() >= self.cells.len() {
+                self.all_active = true;
+                self.written.clear();
+            }
+        }
+        self.population = self.population + state as u32 - self.cells.get(idx) as u32;
+        if !self.ages.is_empty() && self.cells.get(idx) != state {
+            self.ages[idx] = 0;
+        }
+        if !self.trails.is_empty() {
+            self.trails[idx] = if state == Cell::Alive { u8::MAX } else { 0 };
+        }
+        self.cells.set(idx, state);
+        if !self.states.is_empty() {
+            self.states[idx] = state as u8;
+        }
+    }
+
+    fn write_state(&mut self, idx: usize, state: u8) {
+        let state = state.min(self.state_count() - 1);
+        let cell = self.cell_for_state(state);
+        self.write_cell(idx, cell);
+        if !self.states.is_empty() {
+            self.states[idx] = state;
+        }
+    }
+
+    fn cell_for_state(&self, state: u8) -> Cell {
+        if self.mode.is_alive(state) { Cell::Alive } else { Cell::Dead }
+    }
+
+    //Rebuild the state buffer from the alive cells, it only exists for multi-state modes and rules
+    fn reset_states(&mut self) {
+        self.states.clear();
+        if self.mode.has_states(&self.rule, &self.cyclic_rule) {
+            self.states.extend(self.cells.iter().map(|cell| cell as u8));
+        }
+        self.reserve_buffers();
+    }
+
+    //Give the back buffers the size of the current ones so ticking never allocates. Views JS
+    //holds on the old buffers are stale afterwards, which buffer_version tells
+    fn reserve_buffers(&mut self) {
+        self.next.clone_from(&self.cells);
+        self.next_states.clone_from(&self.states);
+        self.buffer_version += 1;
+        self.all_active = true;
+        self.neighbours = NeighbourTable::new(self.width, self.height, self.boundary);
+        self.reset_ages();
+        self.reset_trails();
+    }
+
+    fn reset_ages(&mut self) {
+        self.ages.clear();
+        if self.age_tracking != AgeTracking::Off {
+            self.ages.resize(self.cells.len(), 0);
+        }
+    }
+
+    fn reset_trails(&mut self) {
+        self.trails.clear();
+        if self.trail_decay > 0 {
+            let cells = &self.cells;
+            self.trails.extend(cells.iter().map(|cell| if cell == Cell::Alive { u8::MAX } else { 0 }));
+        }
+    }
+
+    //Run the JS callbacks of set_on_tick and set_on_cell_changed. One that throws is logged and
+    //the tick goes on, the exception has no caller to reach. Once the board grew every index
+    //moved, so all of them are reported
+    fn emit_tick(&self, grew: bool) {
+        let report = |result: Result<JsValue, JsValue>| if let Err(error) = result {
+            web_sys::console::error_1(&error);
+        };
+        if let Some(ref callback) = self.on_tick {
+            report(callback.call2(&JsValue::NULL, &self.generation.into(), &self.population.into()));
+        }
+        if let Some(ref callback) = self.on_cell_changed {
+            if grew {
+                let all: Vec<u32> = (0..self.width * self.height).collect();
+                report(callback.call1(&JsValue::NULL, &Uint32Array::from(&all[..])));
+            } else if !self.changed.is_empty() {
+                report(callback.call1(&JsValue::NULL, &Uint32Array::from(&self.changed[..])));
+            }
+        }
+    }
+
+    fn update_trails(&mut self) {
+        let decay = self.trail_decay;
+        for (idx, trail) in self.trails.iter_mut().enumerate() {
+            *trail = if self.cells.get(idx) == Cell::Alive { u8::MAX } else { trail.saturating_sub(decay) };
+        }
     }
 
-    fn live_neighbour_count(&self, row: u32, column: u32) -> usize {
-        iproduct!([self.height - 1, 0, 1].iter(), [self.width-1, 0, 1].iter())
-            .filter(|(x, y)| **x != 0 || **y != 0)
-            .map(|(x, y)| self.cells[self.get_index((x+row) % self.height, (y+column) % self.width)] as usize)
-            .fold(0, |acc, x| acc+x)
+    //Called by tick once the new generation is in cells and the previous one in next
+    fn update_ages(&mut self) {
+        let dead_too = self.age_tracking == AgeTracking::AliveAndDead;
+        for (idx, age) in self.ages.iter_mut().enumerate() {
+            let cell = self.cells.get(idx);
+            *age = match (cell == self.next.get(idx), cell == Cell::Alive || dead_too) {
+                (_, false) => 0,
+                (true, true) => age.saturating_add(1),
+                (false, true) => 1,
+            };
+        }
     }
 
-    //Tick once
-    pub fn tick(&mut self) {
-        let next = iproduct!(0..self.height, 0..self.width)
-            .map(|(row, col)| {
-                let idx = self.get_index(row, col);
-                let cell = self.cells[idx];
-                let nbr_cnt = self.live_neighbour_count(row, col);
-                
-                log!("Cell [{},{}] is initially {:?} and has {} live neighbors",
-                    row, col, cell, nbr_cnt);
-                
-                let newstate = match (cell, nbr_cnt) {
-                    (Cell::Alive, x) if x < 2 => Cell::Dead,
-                    (Cell::Alive, 2) | (Cell::Alive, 3) => Cell::Alive,
-                    (Cell::Alive, x) if x > 3 => Cell::Dead,
-                    (Cell::Dead, 3) => Cell::Alive,
-                    (otherwise, _) => otherwise,
-                };
-
-                log!(" it becomes {:?}", newstate);
-
-                newstate
-            }).collect();
-        self.cells = next;
-    }
-
-    pub fn new() -> Universe {
-        super::utils::set_panic_hook();
+    //Cell states as bytes row by row, the same for both kinds of rules
+    pub fn state_bytes<'a>(&'a self) -> Box<dyn Iterator<Item = u8> + 'a> {
+        if self.states.is_empty() {
+            Box::new(self.cells.iter().map(|cell| cell as u8))
+        } else {
+            Box::new(self.states.iter().cloned())
+        }
+    }
+
+    //Cells and states of a width x height grid whose cell (row, col) is read at the old
+    //coordinates source(row, col), or dead when that is None
+    fn gather<F>(&self, width: u32, height: u32, source: F) -> (CellBits, Vec<u8>)
+        where F: Fn(u32, u32) -> Option<(u32, u32)> {
+        let mut cells = CellBits::new();
+        let mut states = Vec::new();
+        for (row, col) in iproduct!(0..height, 0..width) {
+            let idx = source(row, col).map(|(r, c)| self.get_index(r, c));
+            cells.push(idx.map_or(Cell::Dead, |idx| self.cells.get(idx)));
+            if !self.states.is_empty() {
+                states.push(idx.map_or(0, |idx| self.states[idx]));
+            }
+        }
+        (cells, states)
+    }
+
+    //Replace the grid with a width x height one built by gather
+    fn rebuild<F>(&mut self, width: u32, height: u32, source: F)
+        where F: Fn(u32, u32) -> Option<(u32, u32)> {
+        let (cells, states) = self.gather(width, height, source);
+        self.width = width;
+        self.height = height;
+        self.population = cells.count_alive();
+        self.cells = cells;
+        self.states = states;
+        self.ant_colors.clear();
+        self.changed.clear();
+        self.reserve_buffers();
+    }
 
-        let width = 64;
-        let height = 64;
-        let cells = (0..width*height).map(|_x| {
-            if js_sys::Math::random() < 0.5 {
-                Cell::Alive
+    //Add rows and columns on the sides where live cells are closer than margin to the edge,
+    //returns whether the board grew
+    fn expand_to_fit(&mut self, margin: u32) -> bool {
+        let (r0, c0, r1, c1) = match self.bounding_box() {
+            Some(bbox) => bbox,
+            None => return false,
+        };
+        let grow = |gap: u32, size: u32, room: u32| if gap < margin { margin.max(size / 2).min(room) } else { 0 };
+        let room_rows = MAX_AUTO_SIZE.saturating_sub(self.height);
+        let top = grow(r0, self.height, room_rows);
+        let bottom = grow(self.height - 1 - r1, self.height, room_rows - top);
+        let room_columns = MAX_AUTO_SIZE.saturating_sub(self.width);
+        let left = grow(c0, self.width, room_columns);
+        let right = grow(self.width - 1 - c1, self.width, room_columns - left);
+        if top + bottom + left + right == 0 {
+            return false;
+        }
+        let (height, width) = (self.height, self.width);
+        self.rebuild(width + left + right, height + top + bottom, |row, col| {
+            if row < top || col < left || row >= top + height || col >= left + width {
+                None
             } else {
-                Cell::Dead
+                Some((row - top, col - left))
             }
-        }).collect();
+        });
+        true
+    }
 
-        Universe {width, height, cells}
+    pub fn rule(&self) -> Rule {
+        self.rule
     }
 
-    pub fn render(&self) -> String {
-        self.to_string()
+    pub fn set_rule(&mut self, rule: Rule) {
+        let had_states = self.mode.has_states(&self.rule, &self.cyclic_rule);
+        self.rule = rule;
+        self.all_active = true;
+        if had_states != self.mode.has_states(&rule, &self.cyclic_rule) {
+            self.reset_states();
+        }
     }
 
-    pub fn width(&self) -> u32 {
-        self.width
+    pub fn ltl_rule(&self) -> LtlRule {
+        self.ltl_rule
     }
 
-    pub fn height(&self) -> u32 {
-        self.height
+    pub fn set_ltl_rule(&mut self, rule: LtlRule) {
+        self.ltl_rule = rule;
+        self.set_mode(Mode::LargerThanLife);
     }
 
-    pub fn cells(&self) -> *const Cell {
-        self.cells.as_ptr()
+    pub fn block_rule(&self) -> BlockRule {
+        self.block_rule
     }
 
-    //Reset all cells to dead after this set 
-    pub fn set_width(&mut self, width: u32) {
-        self.width = width;
-        self.cells = (0..width * self.height).map(|_x| Cell::Dead).collect();
+    pub fn set_block_rule(&mut self, rule: BlockRule) {
+        self.block_rule = rule;
+        self.set_mode(Mode::Margolus);
     }
 
-    //Reset all cells to dead after this reset
-    pub fn set_height(&mut self, height: u32) {
-        self.height = height;
-        self.cells = (0..self.width * height).map(|_x| Cell::Dead).collect();
+    pub fn cyclic_rule(&self) -> CyclicRule {
+        self.cyclic_rule
+    }
+
+    pub fn set_cyclic_rule(&mut self, rule: CyclicRule) {
+        self.cyclic_rule = rule;
+        self.set_mode(Mode::Cyclic);
+    }
+
+    //Unpacked copy of the cells, one Cell each
+    pub fn get_cells(&self) -> Vec<Cell> {
+        self.cells.iter().collect()
+    }
+
+    pub fn set_all_cells(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        if bytes.len() != self.cells.len() {
+            return Err(Error::Invalid(format!("expected {} cells for a {}x{} universe but got {}",
+                self.cells.len(), self.width, self.height, bytes.len())));
+        }
+        self.cells = bytes.iter().map(|&byte| if byte == 0 { Cell::Dead } else { Cell::Alive }).collect();
+        self.generation = 0;
+        self.population = self.cells.count_alive();
+        self.reset_states();
+        Ok(())
+    }
+
+    //Inclusive (min_row, min_col, max_row, max_col) of the alive cells
+    pub fn bounding_box(&self) -> Option<(u32, u32, u32, u32)> {
+        self.cells.ones()
+            .map(|idx| (idx as u32 / self.width, idx as u32 % self.width))
+            .fold(None, |bbox, (row, col)| match bbox {
+                None => Some((row, col, row, col)),
+                Some((r0, c0, r1, c1)) => Some((r0.min(row), c0.min(col), r1.max(row), c1.max(col))),
+            })
+    }
+
+    //Shrink to the bounding box grown by margin on each side (clamped to the board) and return
+    //the (rows, columns) cut from the top-left. An empty universe is left untouched
+    pub fn crop_to_content(&mut self, margin: u32) -> (u32, u32) {
+        let (r0, c0, r1, c1) = match self.bounding_box() {
+            Some(bbox) => bbox,
+            None => return (0, 0),
+        };
+        let (r0, c0) = (r0.saturating_sub(margin), c0.saturating_sub(margin));
+        let r1 = (r1 + margin).min(self.height - 1);
+        let c1 = (c1 + margin).min(self.width - 1);
+
+        self.rebuild(c1 - c0 + 1, r1 - r0 + 1, |row, col| Some((r0 + row, c0 + col)));
+        (r0, c0)
+    }
+
+    //Nothing is set when any of the cells is outside the board
+    pub fn set_cells(&mut self, cells: &[(u32, u32)]) -> Result<(), Error> {
+        let indices = cells.iter().map(|&(r, c)| self.checked_index(r, c)).collect::<Result<Vec<_>, _>>()?;
+        for idx in indices {
+            self.write_cell(idx, Cell::Alive);
+        }
+        Ok(())
     }
 }
 
-//No binding in those implementation functions
-impl Universe {
-    pub fn get_cells(&self) -> &[Cell] {
-        &self.cells
+//Two universes are equal when they have the same dimensions and cells, whatever their generation
+impl PartialEq for Universe {
+    fn eq(&self, other: &Universe) -> bool {
+        self.width == other.width && self.height == other.height && self.state_bytes().eq(other.state_bytes())
     }
+}
+
+impl Eq for Universe {}
 
-    pub fn set_cells(&mut self, cells: &[(u32, u32)]) {
-        for (r, c) in cells {
-            let idx = self.get_index(*r, *c);
-            self.cells[idx] = Cell::Alive;
+impl Hash for Universe {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.width.hash(state);
+        self.height.hash(state);
+        for byte in self.state_bytes() {
+            byte.hash(state);
         }
     }
 }
 
 impl fmt::Display for Universe {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for line in self.cells.as_slice().chunks(self.width as usize) {
-            for &cell in line {
-                let symbol = if cell == Cell::Dead { '◻' } else { '◼' };
-                write!(f, "{}", symbol)?;
-            }
-            write!(f, "\n")?;
-        }       
-        Ok(())
+        let states: Vec<u8> = self.state_bytes().collect();
+        self.glyphs.write_rows(f, self.mode, self.width as usize, &states)
     }
 }
 
@@ -140,6 +1461,35 @@ mod tests {
 
     }
 
+    #[test]
+    fn should_check_the_size_of_new_universes() {
+        assert_eq!(Universe::new(16, 8).map(|univ| (univ.width(), univ.height())), Ok((16, 8)));
+        assert!(Universe::new(0, 8).is_err());
+        assert!(Universe::new(8, 0).is_err());
+        assert!(Universe::new(70000, 70000).is_err());
+        assert!(Universe::new(60000, 60000).is_err());
+        assert!(Universe::check_size(8192, 8192).is_ok() && Universe::check_size(8193, 8192).is_err());
+
+        //Nothing else can make a board without cells either
+        let mut univ = Universe::empty(4, 4);
+        assert!(Universe::empty_js(0, 4).is_err());
+        assert!(univ.resize(0, 4).is_err());
+        assert!(univ.set_width(0).is_err());
+        assert!(univ.copy_region(0, 0, 0, 2).is_err());
+        assert_eq!((univ.width(), univ.height()), (4, 4));
+        assert!("".parse::<Universe>().is_err());
+    }
+
+    #[test]
+    fn should_refuse_cells_past_the_edges() {
+        let mut univ = Universe::with_cells(5, 5, vec![Dead; 25]);
+        assert_eq!(univ.set_cells(&[(1, 1), (2, 7)]), Err(Error::OutOfBounds { row: 2, column: 7, width: 5, height: 5 }));
+        assert_eq!(univ.population(), 0);
+        assert!(univ.set_cells_js(&[1, 1, 2]).is_err());
+        univ.set_cells_js(&[1, 1, 2, 2]).unwrap();
+        assert_eq!(univ.population(), 2);
+    }
+
     #[test]
     fn should_count_correct_neighbrs() {
         let univ = get_universe();
@@ -174,7 +1524,7 @@ mod tests {
     fn should_get_correct_next_tick() {
         let mut univ = get_universe();
         univ.tick();
-        assert_eq!(univ.cells, vec![
+        assert_eq!(univ.get_cells(), vec![
                 Dead,  Dead,  Dead,  Dead,  Dead,
                 Dead,  Alive, Dead,  Dead,  Dead,
                 Dead,  Dead,  Alive, Alive, Dead,
@@ -183,17 +1533,588 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn should_tick_n_generations() {
+        let mut stepped = get_universe();
+        stepped.tick();
+        stepped.tick();
+        stepped.tick();
+        let mut batched = get_universe();
+        batched.tick_n(3);
+        assert_eq!(batched.cells, stepped.cells);
+        assert_eq!(batched.generation(), 3);
+        assert_eq!(stepped.generation(), 3);
+    }
+
+    #[test]
+    fn should_tick_only_active_cells_like_whole_board() {
+        let build = || {
+            let mut univ = Universe::with_cells(32, 32, vec![Dead; 1024]);
+            univ.set_cells(&[(0, 1), (1, 2), (2, 0), (2, 1), (2, 2), (20, 20), (20, 21), (21, 20), (21, 21),
+                (10, 28), (10, 29), (10, 30)]).unwrap();
+            univ
+        };
+        let (mut active, mut full) = (build(), build());
+        for gen in 0..80 {
+            if gen == 30 {
+                active.set_cell(25, 5, Alive);
+                full.set_cell(25, 5, Alive);
+                active.set_cell(25, 6, Alive);
+                full.set_cell(25, 6, Alive);
+            }
+            if gen > 0 {
+                assert!(!active.all_active);
+            }
+            active.tick();
+            full.set_boundary(Boundary::Wrap);
+            full.tick();
+            assert_eq!(active.cells, full.cells);
+            assert_eq!(active.population(), full.population());
+            assert_eq!(active.changed_cells(), full.changed_cells());
+        }
+    }
+
+    #[test]
+    fn should_tick_whole_rows_like_single_cells() {
+        for &boundary in [Boundary::Wrap, Boundary::Dead, Boundary::Mirror].iter() {
+            for &(width, height) in [(37, 5), (1, 3), (16, 2)].iter() {
+                let mut univ = Universe::with_cells(width, height, vec![Dead; (width * height) as usize]);
+                univ.randomize(0.4, u64::from(width));
+                univ.set_boundary(boundary);
+                for rule in ["B36/S23", "B2-a3/S12-k", "B2/S34H", "B1/S1V"].iter() {
+                    univ.set_rule(rule.parse().unwrap());
+                    assert!(univ.rows_apply());
+                    let (mut rows, mut cells) = (CellBits::new(), CellBits::new());
+                    assert_eq!(univ.next_generation_rows(&mut rows), univ.next_generation(&mut cells));
+                    assert_eq!(rows, cells);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn should_count_interior_and_border_neighbours_alike() {
+        let mut univ = Universe::with_cells(7, 6, vec![Dead; 42]);
+        univ.randomize(0.5, 3);
+        for &boundary in [Boundary::Wrap, Boundary::Dead, Boundary::Mirror, Boundary::KleinBottle].iter() {
+            univ.set_boundary(boundary);
+            for (row, col) in iproduct!(0..6, 0..7) {
+                let resolved = MOORE_OFFSETS.iter()
+                    .filter_map(|&(r, c)| boundary.resolve_cell(i64::from(row) + i64::from(r), i64::from(col) + i64::from(c), 6, 7))
+                    .filter(|&(r, c)| univ.get_cell(r as u32, c as u32) == Alive)
+                    .count();
+                assert_eq!(univ.live_neighbour_count(row, col), resolved);
+            }
+        }
+    }
+
+    #[test]
+    fn should_report_memory_of_packed_buffers() {
+        let mut univ = Universe::with_cells(64, 64, vec![Dead; 4096]);
+        let small = univ.memory_usage();
+        assert!(small >= 2 * 4096 / 8);
+        univ.resize(128, 128).unwrap();
+        assert!(univ.memory_usage() >= small + 2 * 3 * 4096 / 8);
+        univ.set_rule("B3/S23/3".parse().unwrap());
+        assert!(univ.memory_usage() >= small + 2 * 4 * 4096);
+    }
+
+    #[test]
+    fn should_swap_persistent_buffers() {
+        let mut univ = get_universe();
+        let (front, version) = (univ.cells(), univ.buffer_version());
+        univ.tick();
+        assert_eq!(univ.front_buffer(), 1);
+        assert_ne!(univ.cells(), front);
+        univ.tick();
+        assert_eq!(univ.front_buffer(), 0);
+        assert_eq!(univ.cells(), front);
+        assert_eq!(univ.buffer_version(), version);
+
+        univ.resize(6, 6).unwrap();
+        assert_ne!(univ.buffer_version(), version);
+    }
+
+    #[test]
+    fn should_run_until_stable() {
+        let mut block = Universe::with_cells(4, 4, vec![Dead; 16]);
+        block.set_cells(&[(1, 1), (1, 2), (2, 1), (2, 2)]).unwrap();
+        assert_eq!(block.run_until_stable(10), 1);
+
+        let mut blinker = Universe::with_cells(5, 5, vec![Dead; 25]);
+        blinker.set_cells(&[(2, 1), (2, 2), (2, 3)]).unwrap();
+        assert_eq!(blinker.run_until_stable(10), 2);
+
+        let mut glider = Universe::with_cells(8, 8, vec![Dead; 64]);
+        glider.set_cells(&[(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]).unwrap();
+        assert_eq!(glider.run_until_stable(5), 5);
+    }
+
+    #[test]
+    fn should_keep_population_up_to_date() {
+        let mut univ = get_universe();
+        assert_eq!(univ.population(), 5);
+        univ.tick();
+        assert_eq!(univ.population(), 4);
+        univ.toggle_cell(0, 0);
+        univ.set_cell(1, 1, Dead);
+        univ.set_cells(&[(2, 2), (4, 4)]).unwrap();
+        assert_eq!(univ.population(), 5);
+        assert_eq!(univ.population() as usize, univ.get_cells().iter().filter(|&&c| c == Alive).count());
+    }
+
+    #[test]
+    fn should_clear_all_cells() {
+        let mut univ = get_universe();
+        univ.tick();
+        univ.clear();
+        assert_eq!(univ.get_cells(), vec![Dead; 25]);
+        assert_eq!((univ.width(), univ.height()), (5, 5));
+        assert_eq!(univ.generation(), 0);
+        assert_eq!(univ.population(), 0);
+    }
+
+    #[test]
+    fn should_randomize_reproducibly() {
+        let mut first = get_universe();
+        let mut second = get_universe();
+        first.tick();
+        first.randomize(0.3, 42);
+        second.randomize(0.3, 42);
+        assert_eq!(first.cells, second.cells);
+        assert_eq!(first.generation(), 0);
+        assert_eq!(first.population() as usize, first.get_cells().iter().filter(|&&c| c == Alive).count());
+
+        first.randomize(0.0, 7);
+        assert_eq!(first.population(), 0);
+        first.randomize(1.0, 7);
+        assert_eq!(first.population(), 25);
+    }
+
+    #[test]
+    fn should_resize_preserving_cells() {
+        let mut grown = get_universe();
+        grown.resize(7, 6).unwrap();
+        assert_eq!((grown.width(), grown.height()), (7, 6));
+        assert_eq!(grown.get_cell(1, 0), Alive);
+        assert_eq!(grown.get_cell(4, 4), Alive);
+        assert_eq!(grown.get_cell(5, 6), Dead);
+        assert_eq!(grown.population(), 5);
+
+        let mut shrunk = get_universe();
+        shrunk.resize_anchored(3, 3, Anchor::BottomRight).unwrap();
+        assert_eq!(shrunk.get_cells(), vec![
+                Alive, Dead, Dead,
+                Alive, Dead, Dead,
+                Dead,  Dead, Alive,
+        ]);
+        assert_eq!(shrunk.population(), 3);
+    }
+
+    #[test]
+    fn should_set_all_cells_from_bytes() {
+        let mut univ = Universe::with_cells(3, 2, vec![Dead; 6]);
+        assert_eq!(univ.set_all_cells(&[1, 0, 0, 0, 2, 1]), Ok(()));
+        assert_eq!(univ.get_cells(), vec![Alive, Dead, Dead, Dead, Alive, Alive]);
+        assert_eq!(univ.population(), 3);
+        assert!(univ.set_all_cells(&[1, 0]).is_err());
+        assert_eq!(univ.population(), 3);
+    }
+
+    #[test]
+    fn should_list_live_cells() {
+        let univ = get_universe();
+        assert_eq!(univ.live_cells(), vec![1, 0, 1, 2, 2, 2, 3, 2, 4, 4]);
+    }
+
+    #[test]
+    fn should_record_changed_cells() {
+        let mut univ = get_universe();
+        assert!(univ.changed_cells().is_empty());
+        univ.tick();
+        assert_eq!(univ.changed_cells(), vec![5, 6, 7, 13, 17, 18, 24]);
+        let view = unsafe { ::std::slice::from_raw_parts(univ.changed_ptr(), univ.changed_len() as usize) };
+        assert_eq!(view, &[5, 6, 7, 13, 17, 18, 24]);
+    }
+
+    #[test]
+    fn should_compute_bounding_box() {
+        let mut univ = get_universe();
+        assert_eq!(univ.bounding_box(), Some((1, 0, 4, 4)));
+        univ.set_cell(1, 0, Dead);
+        univ.set_cell(4, 4, Dead);
+        assert_eq!(univ.bounding_box(), Some((1, 2, 3, 2)));
+        univ.clear();
+        assert_eq!(univ.bounding_box(), None);
+    }
+
+    #[test]
+    fn should_crop_to_content() {
+        let mut univ = get_universe();
+        univ.set_cell(1, 0, Dead);
+        univ.set_cell(4, 4, Dead);
+        assert_eq!(univ.crop_to_content(0), (1, 2));
+        assert_eq!((univ.width(), univ.height()), (1, 3));
+        assert_eq!(univ.get_cells(), vec![Alive; 3]);
+
+        let mut padded = get_universe();
+        padded.set_cell(1, 0, Dead);
+        padded.set_cell(4, 4, Dead);
+        assert_eq!(padded.crop_to_content(1), (0, 1));
+        assert_eq!((padded.width(), padded.height()), (3, 5));
+        assert_eq!(padded.population(), 3);
+    }
+
+    #[test]
+    fn should_compare_and_hash_states() {
+        let mut univ = get_universe();
+        let mut other = get_universe();
+        other.tick();
+        assert_ne!(univ, other);
+        assert_ne!(univ.state_hash(), other.state_hash());
+
+        univ.tick();
+        univ.tick_n(0);
+        assert_eq!(univ, other);
+        assert_eq!(univ.state_hash(), other.state_hash());
+
+        let mut reshaped = Universe::with_cells(25, 1, get_universe().get_cells());
+        assert_ne!(reshaped.state_hash(), get_universe().state_hash());
+        reshaped.clear();
+        assert_ne!(reshaped, Universe::with_cells(5, 5, vec![Dead; 25]));
+    }
+
+    #[test]
+    fn should_tick_with_configured_rule() {
+        let mut seeds = get_universe();
+        seeds.set_rule("B2/S".parse().unwrap());
+        seeds.tick();
+        assert_eq!(seeds.rule_string(), "B2/S");
+        assert_eq!(seeds.get_cells(), vec![
+                Alive, Alive, Dead,  Alive, Alive,
+                Dead,  Dead,  Dead,  Alive, Dead,
+                Dead,  Dead,  Dead,  Dead,  Dead,
+                Dead,  Alive, Dead,  Dead,  Dead,
+                Dead,  Dead,  Dead,  Alive, Dead,
+        ]);
+    }
+
+    #[test]
+    fn should_tick_generations_rule() {
+        let mut brain = Universe::with_cells(6, 4, vec![Dead; 24]);
+        brain.set_preset(RulePreset::BriansBrain);
+        brain.set_cells(&[(1, 1), (2, 1)]).unwrap();
+        brain.tick();
+        assert_eq!(brain.cell_state(1, 1), 2);
+        assert_eq!(brain.get_cell(1, 1), Dead);
+        assert_eq!(brain.live_cells(), vec![1, 0, 1, 2, 2, 0, 2, 2]);
+        assert_eq!(brain.population(), 4);
+        assert_eq!(brain.changed_cells(), vec![6, 7, 8, 12, 13, 14]);
+        assert_eq!(brain.to_string().lines().nth(1), Some("◼▣◼◻◻◻"));
+
+        brain.tick();
+        assert_eq!(brain.cell_state(1, 1), 0);
+        assert_eq!(brain.cell_state(1, 0), 2);
+
+        brain.set_preset(RulePreset::Conway);
+        assert_eq!(brain.state_count(), 2);
+        assert_eq!(brain.cell_state(1, 0), 0);
+    }
+
+    #[test]
+    fn should_tick_wireworld() {
+        let mut wire = Universe::with_cells(6, 3, vec![Dead; 18]);
+        wire.set_mode(Mode::Wireworld);
+        wire.fill_rect(1, 0, 1, 4, Alive);
+        wire.set_cell_state(1, 0, mode::WIRE_TAIL);
+        wire.set_cell_state(1, 1, mode::WIRE_HEAD);
+        assert_eq!(wire.to_string().lines().nth(1), Some("○●◼◼◼◻"));
+
+        wire.tick();
+        assert_eq!(wire.to_string().lines().nth(1), Some("◼○●◼◼◻"));
+        wire.tick_n(2);
+        assert_eq!(wire.to_string().lines().nth(1), Some("◼◼◼○●◻"));
+        assert_eq!(wire.population(), 5);
+        assert_eq!(wire.state_count(), 4);
+
+        wire.set_cell(0, 5, Alive);
+        assert_eq!(wire.cell_state(0, 5), mode::WIRE_CONDUCTOR);
+    }
+
+    #[test]
+    fn should_pass_majority_color_to_newborns() {
+        let mut univ = Universe::with_cells(5, 5, vec![Dead; 25]);
+        univ.set_mode(Mode::Immigration);
+        univ.set_cell_state(1, 2, 2);
+        univ.set_cell_state(2, 2, 1);
+        univ.set_cell_state(3, 2, 2);
+        assert_eq!(univ.state_count(), 3);
+        assert_eq!(univ.population(), 3);
+
+        univ.tick();
+        assert_eq!(univ.to_string().lines().nth(2), Some("◻▣◼▣◻"));
+        assert_eq!(univ.population(), 3);
+    }
+
+    #[test]
+    fn should_give_quadlife_newborns_the_missing_color() {
+        let mut univ = Universe::with_cells(5, 5, vec![Dead; 25]);
+        univ.set_mode(Mode::QuadLife);
+        univ.set_cell_state(1, 2, 1);
+        univ.set_cell_state(2, 2, 2);
+        univ.set_cell_state(3, 2, 3);
+        assert_eq!(univ.state_count(), 5);
+
+        univ.tick();
+        assert_eq!(univ.to_string().lines().nth(2), Some("◻▥▣▥◻"));
+    }
+
+    #[test]
+    fn should_replay_noisy_runs_from_seed() {
+        let mut univ = get_universe();
+        univ.set_noise(0.5, 0.5);
+        univ.set_seed(7);
+        let mut replay = get_universe();
+        replay.set_noise(0.5, 0.5);
+        replay.set_seed(7);
+        univ.tick_n(3);
+        replay.tick_n(3);
+        assert_eq!(univ, replay);
+        assert_eq!(univ.population(), univ.live_cells().len() as u32 / 2);
+
+        let mut frozen = get_universe();
+        frozen.set_noise(0.0, 0.0);
+        frozen.tick();
+        assert_eq!(frozen.population(), 0);
+    }
+
+    #[test]
+    fn should_let_next_state_eat_cyclic_cells() {
+        let mut univ = Universe::with_cells(5, 5, vec![Dead; 25]);
+        univ.set_cyclic(3, 1);
+        univ.set_cell_state(2, 2, 1);
+        univ.tick();
+        assert_eq!(univ.to_string(), "00000\n01110\n01110\n01110\n00000\n");
+        univ.tick();
+        assert_eq!(univ.to_string(), "11111\n11111\n11111\n11111\n11111\n");
+        assert_eq!(univ.palette(), vec![0xFF0000, 0x00FF00, 0x0000FF]);
+    }
+
+    #[test]
+    fn should_tell_neighbour_configurations_apart() {
+        let diagonal = || {
+            let mut univ = Universe::with_cells(5, 5, vec![Dead; 25]);
+            univ.set_cell(1, 3, Alive);
+            univ.set_cell(3, 1, Alive);
+            univ
+        };
+        let mut univ = diagonal();
+        assert_eq!(univ.neighbour_config(2, 2), 0x24);
+        univ.set_rule("B2n/S".parse().unwrap());
+        let mut excluded = diagonal();
+        excluded.set_rule("B2-n/S".parse().unwrap());
+
+        univ.tick();
+        assert_eq!(univ.live_cells(), vec![2, 2]);
+        excluded.tick();
+        assert_eq!(excluded.population(), 0);
+    }
+
+    #[test]
+    fn should_alternate_margolus_blocks() {
+        let mut univ = Universe::with_cells(4, 4, vec![Dead; 16]);
+        univ.set_block_preset(BlockPreset::BilliardBall);
+        univ.set_cell(0, 0, Alive);
+        univ.tick();
+        assert_eq!(univ.live_cells(), vec![1, 1]);
+        univ.tick();
+        assert_eq!(univ.live_cells(), vec![2, 2]);
+        univ.tick_n(2);
+        assert_eq!(univ.live_cells(), vec![0, 0]);
+
+        let mut critters = Universe::with_cells(4, 4, vec![Dead; 16]);
+        critters.set_block_preset(BlockPreset::Critters);
+        critters.tick();
+        assert_eq!(critters.population(), 16);
+        critters.tick();
+        assert_eq!(critters.population(), 0);
+    }
+
+    #[test]
+    fn should_handle_each_boundary() {
+        let mut univ = Universe::with_cells(4, 4, vec![Dead; 16]);
+        univ.set_cell(0, 0, Alive);
+        univ.set_cell(0, 3, Alive);
+        univ.set_cell(3, 0, Alive);
+        assert_eq!(univ.live_neighbour_count(3, 3), 3);
+        assert_eq!(univ.neighbour_index(0, 0, -1, -1), Some(15));
+
+        univ.set_boundary(Boundary::Dead);
+        assert_eq!(univ.live_neighbour_count(3, 3), 0);
+        assert_eq!(univ.neighbour_index(0, 0, -1, -1), None);
+
+        univ.set_boundary(Boundary::Mirror);
+        assert_eq!(univ.live_neighbour_count(0, 1), 2);
+        assert_eq!(univ.neighbour_index(0, 0, -1, -1), Some(0));
+    }
+
+    #[test]
+    fn should_grow_around_expanding_patterns() {
+        let mut univ = Universe::with_cells(8, 8, vec![Dead; 64]);
+        univ.set_auto_expand(2);
+        univ.set_cell(3, 3, Alive);
+        univ.set_cell(3, 4, Alive);
+        univ.set_cell(3, 5, Alive);
+        univ.tick();
+        assert_eq!((univ.width(), univ.height()), (8, 8));
+
+        univ.fill_rect(6, 0, 7, 1, Alive);
+        univ.tick();
+        assert_eq!((univ.width(), univ.height()), (12, 12));
+        assert_eq!(univ.live_cells(), vec![3, 7, 3, 8, 3, 9, 6, 4, 6, 5, 7, 4, 7, 5]);
+        assert_eq!(univ.generation(), 2);
+    }
+
+    #[test]
+    fn should_glue_twisted_edges() {
+        let mut univ = Universe::with_cells(4, 3, vec![Dead; 12]);
+        univ.set_boundary(Boundary::KleinBottle);
+        assert_eq!(univ.neighbour_index(0, 0, -1, 0), Some(2 * 4 + 3));
+        assert_eq!(univ.neighbour_index(0, 0, 0, -1), Some(3));
+        univ.set_boundary(Boundary::CrossSurface);
+        assert_eq!(univ.neighbour_index(0, 0, 0, -1), Some(2 * 4 + 3));
+        assert_eq!(univ.neighbour_index(2, 3, 1, 1), Some(0));
+
+        univ.set_cell(0, 1, Alive);
+        univ.set_ltl_rule("R1,C0,M0,S0..8,B1..1,NM".parse().unwrap());
+        univ.set_boundary(Boundary::KleinBottle);
+        univ.tick();
+        assert_eq!(univ.cell_state(2, 2), 1);
+        assert_eq!(univ.cell_state(2, 0), 0);
+    }
+
+    #[test]
+    fn should_cut_blinker_at_dead_edges() {
+        let mut univ = Universe::with_cells(3, 3, vec![Dead; 9]);
+        univ.set_boundary(Boundary::Dead);
+        univ.fill_rect(0, 0, 0, 2, Alive);
+        univ.tick();
+        assert_eq!(univ.live_cells(), vec![0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn should_scroll_elementary_rows() {
+        let mut univ = Universe::with_cells(7, 3, vec![Dead; 21]);
+        univ.set_mode(Mode::Elementary);
+        univ.set_cell(2, 3, Alive);
+        univ.tick();
+        assert_eq!(univ.to_string(), "◻◻◻◻◻◻◻\n◻◻◻◼◻◻◻\n◻◻◼◼◼◻◻\n");
+        univ.tick();
+        assert_eq!(univ.to_string(), "◻◻◻◼◻◻◻\n◻◻◼◼◼◻◻\n◻◼◼◻◻◼◻\n");
+
+        univ.set_elementary_rule(90);
+        univ.clear();
+        univ.set_cell(2, 0, Alive);
+        univ.tick();
+        assert_eq!(univ.live_cells(), vec![1, 0, 2, 1, 2, 6]);
+    }
+
+    #[test]
+    fn should_count_large_neighbourhoods() {
+        let mut univ = Universe::with_cells(7, 6, vec![Dead; 42]);
+        univ.randomize(0.5, 3);
+        for radius in 1..4 {
+            let counts = univ.box_counts(radius);
+            for (row, col) in iproduct!(0..6, 0..7) {
+                let r = radius as i64;
+                let naive = iproduct!(-r..=r, -r..=r)
+                    .filter(|&(dr, dc)| univ.get_cell(((row + dr) + 60) as u32, ((col + dc) + 70) as u32) == Alive)
+                    .count() as u32;
+                assert_eq!(counts[(row * 7 + col) as usize], naive);
+            }
+        }
+    }
+
+    #[test]
+    fn should_tick_larger_than_life() {
+        let mut univ = get_universe();
+        univ.set_ltl_rule(LtlRule::new(1, false, (3, 3), (2, 3)));
+        assert_eq!(univ.mode(), Mode::LargerThanLife);
+        univ.tick();
+        let mut life = get_universe();
+        life.tick();
+        assert_eq!(univ, life);
+
+        univ.set_ltl_rule(LtlRule::new(2, true, (1, 25), (30, 30)));
+        univ.tick();
+        assert_eq!(univ.population(), 21);
+    }
+
+    #[test]
+    fn should_count_von_neumann_neighbours() {
+        let mut univ = get_universe();
+        univ.set_neighbourhood(Neighbourhood::VonNeumann);
+        assert_eq!(univ.live_neighbour_count(2, 1), 1);
+        assert_eq!(univ.live_neighbour_count(2, 2), 2);
+        assert_eq!(univ.live_neighbour_count(0, 0), 1);
+
+        univ.set_rule("B1/SV".parse().unwrap());
+        univ.clear();
+        univ.set_cell(2, 2, Alive);
+        univ.tick();
+        assert_eq!(univ.live_cells(), vec![1, 2, 2, 1, 2, 3, 3, 2]);
+    }
+
+    #[test]
+    fn should_count_hexagonal_neighbours() {
+        let mut univ = Universe::with_cells(3, 3, vec![Alive; 9]);
+        univ.set_neighbourhood(Neighbourhood::Hexagonal);
+        univ.set_cell(0, 2, Dead);
+        univ.set_cell(2, 0, Dead);
+        assert_eq!(univ.live_neighbour_count(1, 1), 6);
+        univ.set_cell(0, 0, Dead);
+        assert_eq!(univ.live_neighbour_count(1, 1), 5);
+        assert_eq!(univ.rule_string(), "B3/S23H");
+    }
+
+    #[test]
+    fn should_lay_out_hex_centers() {
+        let univ = Universe::with_cells(2, 2, vec![Dead; 4]);
+        let centers = univ.hex_centers(2.0);
+        let step = 2.0 * 3f64.sqrt();
+        assert_eq!(centers.len(), 8);
+        assert_eq!(centers[..4], [step, 2.0, 2.0 * step, 2.0]);
+        assert_eq!(centers[4..], [step / 2.0, 5.0, 1.5 * step, 5.0]);
+    }
+
+    #[test]
+    fn should_toggle_cell_in_place() {
+        let mut univ = get_universe();
+        univ.toggle_cell(0, 0);
+        assert_eq!(univ.cells.get(0), Alive);
+        univ.toggle_cell(1, 0);
+        assert_eq!(univ.cells.get(5), Dead);
+        univ.toggle_cell(5, 6);
+        assert_eq!(univ.cells.get(1), Alive);
+    }
+
+    #[test]
+    fn should_get_and_set_single_cell() {
+        let mut univ = get_universe();
+        assert_eq!(univ.get_cell(1, 0), Alive);
+        univ.set_cell(1, 0, Dead);
+        assert_eq!(univ.get_cell(1, 0), Dead);
+        univ.set_cell(7, 3, Alive);
+        assert_eq!(univ.get_cell(2, 3), Alive);
+    }
+
     fn get_universe() -> Universe {
-        Universe {
-            width: 5,
-            height: 5,
-            cells: vec![
+        Universe::with_cells(5, 5, vec![
                 Dead,  Dead,  Dead,  Dead,  Dead,
                 Alive, Dead,  Alive, Dead,  Dead,
                 Dead,  Dead,  Alive, Dead,  Dead,
                 Dead,  Dead,  Alive, Dead,  Dead,
                 Dead,  Dead,  Dead,  Dead,  Alive,
-            ]
-        }
+        ])
     }
 }
\ No newline at end of file
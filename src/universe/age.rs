@@ -0,0 +1,68 @@
+use wasm_bindgen::prelude::*;
+use error::Error;
+use super::Universe;
+
+//What the age buffer counts. Ages start at 0 when tracking is turned on, when a cell is set by
+//hand and whenever the board is resized or reloaded, and go up by one per generation a cell
+//stays the same
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgeTracking {
+    Off,
+    //Generations alive, dead cells stay at 0
+    Alive,
+    //Generations alive for live cells and generations dead for dead ones
+    AliveAndDead,
+}
+
+#[wasm_bindgen]
+impl Universe {
+    pub fn age_tracking(&self) -> AgeTracking {
+        self.age_tracking
+    }
+
+    pub fn set_age_tracking(&mut self, tracking: AgeTracking) {
+        self.age_tracking = tracking;
+        self.reset_ages();
+    }
+
+    //Ages as a Uint32Array of width * height entries, null while tracking is Off. Each tick
+    //updates it in place, resizing moves it like cells()
+    pub fn ages(&self) -> *const u32 {
+        if self.ages.is_empty() { ::std::ptr::null() } else { self.ages.as_ptr() }
+    }
+
+    //0 while tracking is Off
+    pub fn cell_age(&self, row: u32, column: u32) -> Result<u32, Error> {
+        let idx = self.checked_index(row, column)?;
+        Ok(self.ages.get(idx).cloned().unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Cell::*;
+
+    #[test]
+    fn should_count_generations_in_the_same_state() {
+        let mut univ = Universe::with_cells(5, 5, vec![Dead; 25]);
+        let age = |univ: &Universe, row, column| univ.cell_age(row, column).unwrap();
+        univ.set_cells(&[(2, 1), (2, 2), (2, 3)]).unwrap();
+        assert!(univ.ages().is_null());
+        univ.set_age_tracking(AgeTracking::Alive);
+        univ.tick();
+        univ.tick();
+        //The blinker's middle never dies, its ends come back every other generation
+        assert_eq!((age(&univ, 2, 2), age(&univ, 2, 1), age(&univ, 1, 2), age(&univ, 4, 4)), (2, 1, 0, 0));
+
+        univ.set_age_tracking(AgeTracking::AliveAndDead);
+        univ.tick();
+        assert_eq!((age(&univ, 2, 2), age(&univ, 1, 2), age(&univ, 2, 1), age(&univ, 4, 4)), (1, 1, 1, 1));
+        univ.tick();
+        assert_eq!((age(&univ, 2, 2), age(&univ, 4, 4)), (2, 2));
+        univ.set_cell(4, 4, Alive);
+        assert_eq!(age(&univ, 4, 4), 0);
+        assert_eq!(univ.cell_age(5, 0), Err(Error::OutOfBounds { row: 5, column: 0, width: 5, height: 5 }));
+    }
+}
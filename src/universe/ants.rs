@@ -0,0 +1,126 @@
+use wasm_bindgen::prelude::*;
+use error::Error;
+use ant::{Ant, Direction};
+use super::{Cell, Universe};
+
+#[wasm_bindgen]
+impl Universe {
+    //Put a turmite on the board and return its id, see Ant::new for the rule syntax
+    pub fn add_ant(&mut self, row: u32, column: u32, direction: Direction, rule: &str) -> Result<u32, Error> {
+        let id = self.next_ant_id;
+        let ant = Ant::new(id, row % self.height, column % self.width, direction, rule)?;
+        self.push_ant(ant);
+        Ok(id)
+    }
+
+    //Returns false when there is no ant with that id
+    pub fn remove_ant(&mut self, id: u32) -> bool {
+        let before = self.ants.len();
+        self.ants.retain(|ant| ant.id() != id);
+        self.ants.len() != before
+    }
+
+    //Packed [id, row, column, direction, ...] for every ant, direction as the Direction value
+    pub fn ant_positions(&self) -> Vec<u32> {
+        self.ants.iter()
+            .flat_map(|ant| vec![ant.id(), ant.row(), ant.column(), ant.direction() as u32])
+            .collect()
+    }
+
+    //Move every ant steps times without ticking the cells
+    pub fn step_ants(&mut self, steps: u32) {
+        for _ in 0..steps {
+            for i in 0..self.ants.len() {
+                self.step_ant(i);
+            }
+        }
+    }
+
+    //When set, every tick also moves each ant once, after the cells were updated
+    pub fn set_ants_follow_tick(&mut self, follow: bool) {
+        self.ants_follow_tick = follow;
+    }
+}
+
+impl Universe {
+    pub fn ants(&self) -> &[Ant] {
+        &self.ants
+    }
+
+    pub fn push_ant(&mut self, ant: Ant) {
+        self.next_ant_id = self.next_ant_id.max(ant.id() + 1);
+        self.ants.push(ant);
+    }
+
+    fn step_ant(&mut self, i: usize) {
+        let idx = self.wrapped_index(self.ants[i].row(), self.ants[i].column());
+        let color = self.ant_color(idx);
+        let (height, width) = (self.height, self.width);
+        let color = self.ants[i].step(color, height, width);
+        self.paint(idx, color);
+    }
+
+    //Ants with more than two colors keep the extra colors aside, a cell is only alive while its color is not 0.
+    //Cells killed by a tick go back to color 0 and cells born in it start at color 1
+    fn ant_color(&self, idx: usize) -> u8 {
+        match (self.cells.get(idx), self.ant_colors.get(idx)) {
+            (Cell::Dead, _) => 0,
+            (Cell::Alive, Some(&color)) if color > 0 => color,
+            (Cell::Alive, _) => 1,
+        }
+    }
+
+    fn paint(&mut self, idx: usize, color: u8) {
+        if color > 1 && self.ant_colors.len() != self.cells.len() {
+            self.ant_colors = self.cells.iter().map(|cell| cell as u8).collect();
+        }
+        if let Some(slot) = self.ant_colors.get_mut(idx) {
+            *slot = color;
+        }
+        self.write_cell(idx, if color == 0 { Cell::Dead } else { Cell::Alive });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ant::Direction;
+    use super::super::Universe;
+    use super::super::Cell::*;
+
+    #[test]
+    fn should_walk_langtons_ant() {
+        let mut univ = Universe::with_cells(5, 5, vec![Dead; 25]);
+        let id = univ.add_ant(2, 2, Direction::Up, "RL").unwrap();
+        univ.step_ants(4);
+        assert_eq!(univ.live_cells(), vec![2, 2, 2, 3, 3, 2, 3, 3]);
+        assert_eq!(univ.ant_positions(), vec![id, 2, 2, Direction::Up as u32]);
+        univ.step_ants(1);
+        assert_eq!(univ.get_cell(2, 2), Dead);
+        assert!(univ.remove_ant(id));
+        assert!(!univ.remove_ant(id));
+    }
+
+    #[test]
+    fn should_cycle_extra_colors() {
+        let mut univ = Universe::with_cells(3, 3, vec![Dead; 9]);
+        univ.add_ant(1, 1, Direction::Up, "RLR").unwrap();
+        univ.step_ants(1);
+        univ.set_cell(1, 1, Dead);
+        univ.step_ants(3);
+        assert_eq!(univ.ant_positions()[1..3], [1, 1]);
+        univ.step_ants(1);
+        assert_eq!(univ.ant_color(4), 1);
+        assert_eq!(univ.population(), 4);
+    }
+
+    #[test]
+    fn should_step_ants_with_tick() {
+        let mut univ = Universe::with_cells(5, 5, vec![Dead; 25]);
+        univ.add_ant(0, 0, Direction::Up, "RL").unwrap();
+        univ.tick();
+        assert_eq!(univ.population(), 0);
+        univ.set_ants_follow_tick(true);
+        univ.tick();
+        assert_eq!(univ.live_cells(), vec![0, 0]);
+    }
+}
@@ -0,0 +1,207 @@
+use std::iter::FromIterator;
+use super::Cell;
+use utils::vec_bytes;
+
+pub const WORD_BITS: usize = 64;
+
+//Cells packed one bit each (1 alive), cell idx is bit idx % 64 of word idx / 64. Bits past the
+//last cell are always 0 so whole words can be compared and counted
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct CellBits {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl CellBits {
+    pub fn new() -> CellBits {
+        CellBits::default()
+    }
+
+    pub fn filled(len: usize, cell: Cell) -> CellBits {
+        let mut bits = CellBits::new();
+        bits.resize(len, cell);
+        bits
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    //Heap bytes reserved for the words
+    pub fn memory_usage(&self) -> usize {
+        vec_bytes(&self.words)
+    }
+
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    pub fn get(&self, idx: usize) -> Cell {
+        debug_assert!(idx < self.len);
+        if self.words[idx / WORD_BITS] >> (idx % WORD_BITS) & 1 == 1 { Cell::Alive } else { Cell::Dead }
+    }
+
+    pub fn set(&mut self, idx: usize, cell: Cell) {
+        debug_assert!(idx < self.len);
+        let (word, mask) = (idx / WORD_BITS, 1u64 << (idx % WORD_BITS));
+        match cell {
+            Cell::Alive => self.words[word] |= mask,
+            Cell::Dead => self.words[word] &= !mask,
+        }
+    }
+
+    pub fn push(&mut self, cell: Cell) {
+        if self.len.is_multiple_of(WORD_BITS) {
+            self.words.push(0);
+        }
+        self.len += 1;
+        let idx = self.len - 1;
+        self.set(idx, cell);
+    }
+
+    pub fn clear(&mut self) {
+        self.words.clear();
+        self.len = 0;
+    }
+
+    pub fn fill(&mut self, cell: Cell) {
+        let len = self.len;
+        self.clear();
+        self.resize(len, cell);
+    }
+
+    //Grow with copies of cell or truncate
+    pub fn resize(&mut self, len: usize, cell: Cell) {
+        if len < self.len {
+            self.words.truncate(len.div_ceil(WORD_BITS));
+            if !len.is_multiple_of(WORD_BITS) {
+                let last = self.words.len() - 1;
+                self.words[last] &= (1 << (len % WORD_BITS)) - 1;
+            }
+            self.len = len;
+        } else if cell == Cell::Dead {
+            self.words.resize(len.div_ceil(WORD_BITS), 0);
+            self.len = len;
+        } else {
+            while self.len < len {
+                self.push(cell);
+            }
+        }
+    }
+
+    pub fn count_alive(&self) -> u32 {
+        self.words.iter().map(|word| word.count_ones()).sum()
+    }
+
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = Cell> + 'a {
+        (0..self.len).map(move |idx| self.get(idx))
+    }
+
+    //Indices of the alive cells in increasing order
+    pub fn ones<'a>(&'a self) -> impl Iterator<Item = usize> + 'a {
+        Ones { words: &self.words, word: 0, bits: self.words.first().cloned().unwrap_or(0) }
+    }
+
+    //Append the indices where the two buffers differ, a word at a time
+    pub fn changed(&self, before: &CellBits, changed: &mut Vec<u32>) {
+        for (word, (now, before)) in self.words.iter().zip(&before.words).enumerate() {
+            let mut bits = now ^ before;
+            while bits != 0 {
+                changed.push((word * WORD_BITS) as u32 + bits.trailing_zeros());
+                bits &= bits - 1;
+            }
+        }
+    }
+
+    //(born, died) counts of cells going from before to these
+    pub fn transitions(&self, before: &CellBits) -> (u32, u32) {
+        self.words.iter().zip(&before.words)
+            .fold((0, 0), |(born, died), (now, before)| (born + (now & !before).count_ones(), died + (before & !now).count_ones()))
+    }
+
+    //Kill the alive cells keep(idx) turns down
+    pub fn retain_alive<F: FnMut(usize) -> bool>(&mut self, mut keep: F) {
+        for (word, bits) in self.words.iter_mut().enumerate() {
+            let mut alive = *bits;
+            while alive != 0 {
+                let bit = alive.trailing_zeros() as usize;
+                alive &= alive - 1;
+                if !keep(word * WORD_BITS + bit) {
+                    *bits &= !(1 << bit);
+                }
+            }
+        }
+    }
+}
+
+struct Ones<'a> {
+    words: &'a [u64],
+    word: usize,
+    bits: u64,
+}
+
+impl<'a> Iterator for Ones<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.bits == 0 {
+            self.word += 1;
+            self.bits = *self.words.get(self.word)?;
+        }
+        let bit = self.bits.trailing_zeros() as usize;
+        self.bits &= self.bits - 1;
+        Some(self.word * WORD_BITS + bit)
+    }
+}
+
+impl Extend<Cell> for CellBits {
+    fn extend<I: IntoIterator<Item = Cell>>(&mut self, cells: I) {
+        for cell in cells {
+            self.push(cell);
+        }
+    }
+}
+
+impl FromIterator<Cell> for CellBits {
+    fn from_iter<I: IntoIterator<Item = Cell>>(cells: I) -> CellBits {
+        let mut bits = CellBits::new();
+        bits.extend(cells);
+        bits
+    }
+}
+
+impl From<Vec<Cell>> for CellBits {
+    fn from(cells: Vec<Cell>) -> CellBits {
+        cells.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Cell::*;
+
+    #[test]
+    fn should_pack_cells_into_words() {
+        let mut bits = CellBits::filled(130, Dead);
+        bits.set(0, Alive);
+        bits.set(64, Alive);
+        bits.set(129, Alive);
+        assert_eq!(bits.words(), &[1, 1, 2]);
+        assert_eq!(bits.ones().collect::<Vec<_>>(), vec![0, 64, 129]);
+        assert_eq!(bits.count_alive(), 3);
+
+        bits.resize(65, Alive);
+        assert_eq!(bits.words(), &[1, 1]);
+        bits.resize(67, Alive);
+        assert_eq!(bits.iter().skip(63).collect::<Vec<_>>(), vec![Dead, Alive, Alive, Alive]);
+
+        let mut changed = Vec::new();
+        bits.changed(&CellBits::filled(67, Dead), &mut changed);
+        assert_eq!(changed, vec![0, 64, 65, 66]);
+        assert_eq!(CellBits::filled(67, Dead).transitions(&bits), (0, 4));
+        bits.retain_alive(|idx| idx % 2 == 0);
+        assert_eq!(bits.ones().collect::<Vec<_>>(), vec![0, 64, 66]);
+        assert_eq!(bits.iter().collect::<CellBits>(), bits);
+    }
+}
@@ -0,0 +1,95 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use formats::parse_pattern;
+use rule::Rule;
+use error::Error;
+use rng;
+use super::{Boundary, Universe};
+
+//Everything a new universe can start with, from a plain JS object where every field is optional:
+//Universe.with_config({width: 128, height: 96, rule: "B36/S23", boundary: "Mirror", seed: 42}).
+//Without a pattern the board is a soup of the given density (0.5 by default) from the seed (a
+//random one by default); with one the board starts empty unless density says otherwise and the
+//pattern, in any format parse_pattern reads, is centred on it. The rule defaults to the
+//pattern's own, then Conway's
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UniverseConfig {
+    pub width: u32,
+    pub height: u32,
+    pub rule: Option<String>,
+    pub boundary: Boundary,
+    pub seed: Option<u64>,
+    pub density: Option<f64>,
+    pub pattern: Option<String>,
+}
+
+impl Default for UniverseConfig {
+    fn default() -> UniverseConfig {
+        UniverseConfig { width: 64, height: 64, rule: None, boundary: Boundary::Wrap, seed: None, density: None, pattern: None }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    //Universe from a config object, see UniverseConfig. Unknown fields and bad values are errors
+    #[wasm_bindgen(js_name = with_config)]
+    pub fn with_config_js(config: JsValue) -> Result<Universe, Error> {
+        let config: UniverseConfig = serde_wasm_bindgen::from_value(config).map_err(|e| Error::Invalid(e.to_string()))?;
+        Universe::with_config(&config)
+    }
+}
+
+impl Universe {
+    pub fn with_config(config: &UniverseConfig) -> Result<Universe, Error> {
+        Universe::check_size(config.width, config.height)?;
+        let pattern = config.pattern.as_ref().map(|text| parse_pattern(text)).transpose()?;
+        let density = config.density.unwrap_or(if pattern.is_some() { 0.0 } else { 0.5 });
+        if !(0.0..=1.0).contains(&density) {
+            return Err(Error::Invalid(format!("density {} is not between 0 and 1", density)));
+        }
+
+        let mut universe = Universe::empty(config.width, config.height);
+        match (&config.rule, pattern.as_ref().and_then(|pattern| pattern.info.rule)) {
+            (Some(rule), _) => universe.set_rule(rule.parse::<Rule>()?),
+            (None, Some(rule)) => universe.set_rule(rule),
+            (None, None) => {}
+        }
+        universe.set_boundary(config.boundary);
+        if density > 0.0 {
+            universe.randomize(density, config.seed.unwrap_or_else(rng::random_seed));
+        }
+        if let Some(pattern) = pattern {
+            if pattern.width() > config.width || pattern.height() > config.height {
+                return Err(Error::Invalid(format!("the {}x{} pattern doesn't fit a {}x{} universe", pattern.width(), pattern.height(), config.width, config.height)));
+            }
+            universe.insert_pattern(&pattern, (config.height - pattern.height()) / 2, (config.width - pattern.width()) / 2);
+        }
+        Ok(universe)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use universe::Cell;
+
+    #[test]
+    fn should_build_from_config() {
+        let soup = |seed| Universe::with_config(&UniverseConfig { width: 32, height: 16, seed: Some(seed), ..Default::default() }).unwrap();
+        assert_eq!(soup(7).width(), 32);
+        assert!(soup(7).population() > 0);
+        assert_eq!(soup(7), soup(7));
+
+        let config: UniverseConfig = serde_json::from_str(r#"{"width": 8, "height": 8, "boundary": "Dead", "pattern": "x = 3, y = 1, rule = B36/S23\n3o!"}"#).unwrap();
+        let blinker = Universe::with_config(&config).unwrap();
+        assert_eq!(blinker.population(), 3);
+        assert_eq!(blinker.get_cell(3, 2), Cell::Alive);
+        assert_eq!(blinker.rule_string(), "B36/S23");
+        assert_eq!(blinker.boundary, Boundary::Dead);
+
+        assert!(serde_json::from_str::<UniverseConfig>(r#"{"size": 8}"#).is_err());
+        assert!(Universe::with_config(&UniverseConfig { density: Some(2.0), ..Default::default() }).is_err());
+        assert!(Universe::with_config(&UniverseConfig { width: 2, pattern: Some("3o!".to_string()), ..Default::default() }).is_err());
+    }
+}
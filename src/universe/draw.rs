@@ -0,0 +1,225 @@
+use wasm_bindgen::prelude::*;
+use pattern::{Pattern, Transform};
+use super::{Cell, Universe};
+
+//Indices from start to end inclusive, clamped to size and wrapping past the edge when start > end
+fn span(start: u32, end: u32, size: u32) -> Vec<u32> {
+    let (start, end) = (start.min(size - 1), end.min(size - 1));
+    if start <= end {
+        (start..=end).collect()
+    } else {
+        (start..size).chain(0..=end).collect()
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    //Set every cell of the rectangle between the two corners (inclusive) to state. Corners are
+    //clamped to the board, and a rectangle whose start is past its end wraps across the edge
+    pub fn fill_rect(&mut self, r0: u32, c0: u32, r1: u32, c1: u32, state: Cell) {
+        let columns = span(c0, c1, self.width);
+        for row in span(r0, r1, self.height) {
+            for &col in &columns {
+                let idx = self.get_index(row, col);
+                self.write_cell(idx, state);
+            }
+        }
+    }
+
+    pub fn clear_rect(&mut self, r0: u32, c0: u32, r1: u32, c1: u32) {
+        self.fill_rect(r0, c0, r1, c1, Cell::Dead);
+    }
+
+    //Paint the region of same-state cells connected to (row, col) with state. Cells are connected
+    //through their edges, or also through their corners when eight_connected is set; the region
+    //wraps around the board edges like the tick neighbourhood
+    pub fn flood_fill(&mut self, row: u32, column: u32, state: Cell, eight_connected: bool) {
+        let start = self.wrapped_index(row, column);
+        let target = self.cells.get(start);
+        if target == state {
+            return;
+        }
+        let (height, width) = (self.height, self.width);
+        self.write_cell(start, state);
+        let mut pending = vec![(row % height, column % width)];
+        while let Some((r, c)) = pending.pop() {
+            for (dr, dc) in iproduct!(&[height - 1, 0, 1], &[width - 1, 0, 1]) {
+                if (*dr == 0 && *dc == 0) || (!eight_connected && *dr != 0 && *dc != 0) {
+                    continue;
+                }
+                let (nr, nc) = ((r + dr) % height, (c + dc) % width);
+                let idx = self.get_index(nr, nc);
+                if self.cells.get(idx) == target {
+                    self.write_cell(idx, state);
+                    pending.push((nr, nc));
+                }
+            }
+        }
+    }
+
+    //Bresenham line between the two cells (inclusive), used to join the points of a pointer drag.
+    //The endpoints are ordered first so a line covers the same cells whichever way it is drawn
+    pub fn draw_line(&mut self, r0: u32, c0: u32, r1: u32, c1: u32, state: Cell) {
+        let ((r0, c0), (r1, c1)) = if (r0, c0) <= (r1, c1) {
+            ((r0, c0), (r1, c1))
+        } else {
+            ((r1, c1), (r0, c0))
+        };
+        let (mut r, mut c) = (i64::from(r0), i64::from(c0));
+        let (r1, c1) = (i64::from(r1), i64::from(c1));
+        let (dr, dc) = (-(r1 - r).abs(), (c1 - c).abs());
+        let (step_r, step_c) = (if r < r1 { 1 } else { -1 }, if c < c1 { 1 } else { -1 });
+        let mut err = dc + dr;
+        loop {
+            let idx = self.wrapped_index(r as u32, c as u32);
+            self.write_cell(idx, state);
+            if r == r1 && c == c1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dr {
+                err += dr;
+                c += step_c;
+            }
+            if e2 <= dc {
+                err += dc;
+                r += step_r;
+            }
+        }
+    }
+
+    //Ellipse centred on (row, column) with the given radii, either filled or just its outline.
+    //The shape wraps around the board edges
+    pub fn draw_ellipse(&mut self, row: u32, column: u32, radius_rows: u32, radius_cols: u32,
+                        state: Cell, filled: bool) {
+        let (ry, rx) = (i64::from(radius_rows), i64::from(radius_cols));
+        let (a, b) = (rx as f64 + 0.5, ry as f64 + 0.5);
+        let inside = |dy: i64, dx: i64| {
+            let (x, y) = (dx as f64 / a, dy as f64 / b);
+            x * x + y * y <= 1.0
+        };
+        let (height, width) = (i64::from(self.height), i64::from(self.width));
+        for (dy, dx) in iproduct!(-ry..=ry, -rx..=rx) {
+            if !inside(dy, dx) {
+                continue;
+            }
+            let on_edge = !inside(dy - 1, dx) || !inside(dy + 1, dx) || !inside(dy, dx - 1) || !inside(dy, dx + 1);
+            if filled || on_edge {
+                let r = (i64::from(row) + dy).rem_euclid(height) as u32;
+                let c = (i64::from(column) + dx).rem_euclid(width) as u32;
+                let idx = self.get_index(r, c);
+                self.write_cell(idx, state);
+            }
+        }
+    }
+
+    pub fn draw_circle(&mut self, row: u32, column: u32, radius: u32, state: Cell, filled: bool) {
+        self.draw_ellipse(row, column, radius, radius, state, filled);
+    }
+
+    //Overwrite the box starting at (row, column) with the transformed pattern, dead cells included.
+    //The box wraps around the board edges
+    pub fn stamp(&mut self, pattern: &Pattern, row: u32, column: u32, transform: Transform) {
+        let (height, width) = (pattern.height(), pattern.width());
+        for (r, c) in iproduct!(0..height, 0..width) {
+            let (tr, tc) = transform.apply(r, c, height, width);
+            let idx = self.wrapped_index(row + tr, column + tc);
+            self.write_cell(idx, pattern.get(r, c));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pattern::{Pattern, Transform};
+    use super::super::Universe;
+    use super::super::Cell::*;
+
+    #[test]
+    fn should_fill_and_clear_rect() {
+        let mut univ = Universe::with_cells(4, 3, vec![Dead; 12]);
+        univ.fill_rect(0, 1, 1, 9, Alive);
+        assert_eq!(univ.get_cells(), &[
+            Dead, Alive, Alive, Alive,
+            Dead, Alive, Alive, Alive,
+            Dead, Dead,  Dead,  Dead,
+        ]);
+        assert_eq!(univ.population(), 6);
+
+        univ.clear_rect(2, 3, 0, 1);
+        assert_eq!(univ.get_cells(), &[
+            Dead, Dead,  Alive, Dead,
+            Dead, Alive, Alive, Alive,
+            Dead, Dead,  Dead,  Dead,
+        ]);
+        assert_eq!(univ.population(), 4);
+    }
+
+    #[test]
+    fn should_flood_fill_connected_region() {
+        let ring = vec![
+            Dead, Dead,  Dead,  Dead,  Dead,  Dead,
+            Dead, Alive, Alive, Alive, Dead,  Dead,
+            Dead, Alive, Dead,  Alive, Dead,  Dead,
+            Dead, Alive, Alive, Dead,  Dead,  Dead,
+            Dead, Dead,  Dead,  Dead,  Dead,  Dead,
+        ];
+        let mut inside = Universe::with_cells(6, 5, ring.clone());
+        inside.flood_fill(2, 2, Alive, false);
+        assert_eq!(inside.get_cell(2, 2), Alive);
+        assert_eq!(inside.get_cell(0, 0), Dead);
+        assert_eq!(inside.population(), 8);
+
+        let mut leaking = Universe::with_cells(6, 5, ring);
+        leaking.flood_fill(2, 2, Alive, true);
+        assert_eq!(leaking.population(), 30);
+    }
+
+    #[test]
+    fn should_draw_continuous_line() {
+        let mut univ = Universe::with_cells(5, 3, vec![Dead; 15]);
+        univ.draw_line(0, 0, 2, 4, Alive);
+        assert_eq!(univ.get_cells(), &[
+            Alive, Dead,  Dead,  Dead,  Dead,
+            Dead,  Alive, Alive, Dead,  Dead,
+            Dead,  Dead,  Dead,  Alive, Alive,
+        ]);
+
+        univ.draw_line(2, 4, 0, 0, Dead);
+        assert_eq!(univ.population(), 0);
+    }
+
+    #[test]
+    fn should_draw_circles_and_ellipses() {
+        let mut filled = Universe::with_cells(7, 7, vec![Dead; 49]);
+        filled.draw_circle(3, 3, 2, Alive, true);
+        assert_eq!(filled.population(), 21);
+
+        let mut outline = Universe::with_cells(7, 7, vec![Dead; 49]);
+        outline.draw_circle(3, 3, 2, Alive, false);
+        assert_eq!(outline.population(), 12);
+        assert_eq!(outline.get_cell(3, 3), Dead);
+        assert_eq!(outline.get_cell(1, 3), Alive);
+
+        let mut wrapped = Universe::with_cells(6, 4, vec![Dead; 24]);
+        wrapped.draw_ellipse(0, 0, 0, 2, Alive, true);
+        assert_eq!(wrapped.get_cells()[..6], [Alive, Alive, Alive, Dead, Alive, Alive]);
+        assert_eq!(wrapped.population(), 5);
+    }
+
+    #[test]
+    fn should_stamp_transformed_pattern() {
+        let glider = Pattern::from_coords(&[(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]).unwrap();
+        let mut univ = Universe::with_cells(5, 5, vec![Alive; 25]);
+        univ.stamp(&glider, 1, 1, Transform::Identity);
+        assert_eq!(univ.get_cells()[5..15], [
+            Alive, Dead, Alive, Dead,  Alive,
+            Alive, Dead, Dead,  Alive, Alive,
+        ]);
+        assert_eq!(univ.population(), 21);
+
+        let mut rotated = Universe::with_cells(5, 5, vec![Dead; 25]);
+        rotated.stamp(&glider, 3, 3, Transform::Rotate90);
+        assert_eq!(rotated.live_cells(), vec![0, 3, 0, 4, 3, 3, 4, 0, 4, 3]);
+    }
+}
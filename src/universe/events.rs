@@ -0,0 +1,23 @@
+use wasm_bindgen::prelude::*;
+use js_sys::Function;
+use super::Universe;
+
+#[wasm_bindgen]
+impl Universe {
+    //Call callback(generation, population) after every tick, tick_n and run_until_stable
+    //included, so pages can update counters without polling. null or undefined removes it.
+    //The universe is still borrowed by the tick while the callback runs, so calling any of its
+    //methods from there throws "recursive use of an object"; work from the arguments or put the
+    //call off with queueMicrotask
+    pub fn set_on_tick(&mut self, callback: Option<Function>) {
+        self.on_tick = callback;
+    }
+
+    //Call callback(indices) after every tick that flipped cells, with a Uint32Array copy of
+    //changed_cells() that stays valid after further ticks. A tick that grew the board (see
+    //set_auto_expand) passes every index of the new one instead. null or undefined removes it,
+    //and the same borrow rule as set_on_tick applies
+    pub fn set_on_cell_changed(&mut self, callback: Option<Function>) {
+        self.on_cell_changed = callback;
+    }
+}
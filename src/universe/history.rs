@@ -0,0 +1,93 @@
+use wasm_bindgen::prelude::*;
+use std::collections::VecDeque;
+use std::mem;
+use super::Universe;
+
+//Population, births and deaths of the last capacity generations, oldest first
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct History {
+    capacity: usize,
+    population: VecDeque<u32>,
+    births: VecDeque<u32>,
+    deaths: VecDeque<u32>,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> History {
+        History { capacity, ..History::default() }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn push(&mut self, population: u32, births: u32, deaths: u32) {
+        let capacity = self.capacity;
+        let push = |values: &mut VecDeque<u32>, value: u32| {
+            if values.len() == capacity {
+                values.pop_front();
+            }
+            values.push_back(value);
+        };
+        push(&mut self.population, population);
+        push(&mut self.births, births);
+        push(&mut self.deaths, deaths);
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        3 * self.population.capacity() * mem::size_of::<u32>()
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    //Remember population, births and deaths of the last length generations ticked, 0 (the
+    //default) forgets them. Changing the length starts the history over
+    pub fn set_history_length(&mut self, length: u32) {
+        self.history = History::new(length as usize);
+    }
+
+    pub fn history_length(&self) -> u32 {
+        self.history.capacity() as u32
+    }
+
+    //Population after each remembered generation as a Uint32Array, oldest first
+    pub fn population_history(&self) -> Vec<u32> {
+        self.history.population.iter().cloned().collect()
+    }
+
+    //Cells born in each remembered generation
+    pub fn birth_history(&self) -> Vec<u32> {
+        self.history.births.iter().cloned().collect()
+    }
+
+    //Cells that died in each remembered generation
+    pub fn death_history(&self) -> Vec<u32> {
+        self.history.deaths.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Cell::*;
+
+    #[test]
+    fn should_keep_last_generations_of_stats() {
+        let mut univ = Universe::with_cells(8, 8, vec![Dead; 64]);
+        univ.set_cells(&[(2, 1), (2, 2), (2, 3), (5, 5)]).unwrap();
+        univ.tick();
+        assert!(univ.population_history().is_empty());
+        univ.set_history_length(2);
+        univ.tick();
+        assert_eq!((univ.population_history(), univ.birth_history(), univ.death_history()), (vec![3], vec![2], vec![2]));
+        univ.set_cell(6, 6, Alive);
+        univ.tick();
+        univ.tick();
+        assert_eq!(univ.population_history(), vec![3, 3]);
+        assert_eq!((univ.birth_history(), univ.death_history()), (vec![2, 2], vec![3, 2]));
+        univ.set_history_length(0);
+        univ.tick();
+        assert!(univ.death_history().is_empty());
+    }
+}
@@ -0,0 +1,288 @@
+use wasm_bindgen::prelude::*;
+#[cfg(any(feature = "png-export", feature = "gif-export"))]
+use error::Error;
+use std::fmt::Write;
+use std::mem;
+use renderer::{css_color, rgba, Palette};
+use super::Universe;
+
+#[wasm_bindgen]
+impl Universe {
+    //Colours for every built-in renderer: Canvas2d, WebGl2, render_rgba, to_svg, to_png, record_gif
+    pub fn set_palette(&mut self, palette: &Palette) {
+        self.custom_palette = Some(palette.clone());
+    }
+
+    //Back to the mode's colours
+    pub fn reset_palette(&mut self) {
+        self.custom_palette = None;
+    }
+
+    pub fn custom_palette(&self) -> Option<Palette> {
+        self.custom_palette.clone()
+    }
+
+    //palette() followed by the colours of the palette's age stops
+    pub fn color_table(&self) -> Vec<u32> {
+        let mut colors = self.palette();
+        if let Some(ref palette) = self.custom_palette {
+            colors.extend(palette.stop_colors());
+        }
+        colors
+    }
+
+    //Index into color_table() of every cell, row by row: the cell state, or for live cells that
+    //reached an age stop the stop's entry
+    #[wasm_bindgen(js_name = color_indices)]
+    pub fn color_indices_js(&self) -> Vec<u8> {
+        self.color_indices().collect()
+    }
+}
+
+impl Universe {
+    pub fn color_indices<'a>(&'a self) -> Box<dyn Iterator<Item = u8> + 'a> {
+        match self.custom_palette {
+            Some(ref palette) if !self.ages.is_empty() && palette.stop_colors().next().is_some() => {
+                let first = u32::from(self.state_count());
+                Box::new(self.state_bytes().zip(&self.ages).map(move |(state, &age)| match (state, palette.stop(age)) {
+                    (1, Some(stop)) => (first + stop as u32).min(255) as u8,
+                    _ => state,
+                }))
+            }
+            _ => self.state_bytes(),
+        }
+    }
+
+    //RGBA frame of the board with cell_size x cell_size pixels per cell in the color_table() colours,
+    //(width * cell_size) x (height * cell_size) pixels row by row
+    pub fn render_rgba(&self, cell_size: u32) -> Vec<u8> {
+        let mut frame = Vec::new();
+        self.render_rgba_into(cell_size, &mut frame);
+        frame
+    }
+
+    fn render_rgba_into(&self, cell_size: u32, frame: &mut Vec<u8>) {
+        let colors: Vec<Vec<u8>> = self.color_table().into_iter().map(|color| rgba(color).to_vec()).collect();
+        self.render_into(cell_size, &colors, frame);
+    }
+
+    //cell_size x cell_size pixels per cell made of the bytes pixels holds for its colour index.
+    //Each row of cells is drawn once and its first pixel line copied down the rest of the cell
+    fn render_into(&self, cell_size: u32, pixels: &[Vec<u8>], frame: &mut Vec<u8>) {
+        let size = cell_size.max(1) as usize;
+        let states: Vec<u8> = self.color_indices().collect();
+        frame.clear();
+        frame.reserve(states.len() * size * size * pixels[0].len());
+        for row in states.chunks(self.width.max(1) as usize) {
+            let line = frame.len();
+            for &state in row {
+                let pixel = &pixels[(state as usize).min(pixels.len() - 1)];
+                for _ in 0..size {
+                    frame.extend_from_slice(pixel);
+                }
+            }
+            let end = frame.len();
+            for _ in 1..size {
+                frame.extend_from_within(line..end);
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    //render_rgba as a view into wasm memory rather than a copy, ready for
+    //new ImageData(view, width * cell_size, height * cell_size). The next call overwrites it and
+    //it goes stale when wasm memory grows, so draw it right away
+    #[wasm_bindgen(js_name = render_rgba)]
+    pub fn render_rgba_js(&mut self, cell_size: u32) -> js_sys::Uint8ClampedArray {
+        let mut frame = mem::take(&mut self.frame);
+        self.render_rgba_into(cell_size, &mut frame);
+        self.frame = frame;
+        unsafe { js_sys::Uint8ClampedArray::view(&self.frame) }
+    }
+
+    //SVG document of the board, cell_size units per cell. palette holds the 0xRRGGBB colour of
+    //each colour index like Canvas2d::set_colors, empty for the color_table() colours. Index 0 is
+    //the background and runs of cells of the same colour along a row share one rect
+    pub fn to_svg(&self, cell_size: u32, palette: &[u32]) -> String {
+        let size = cell_size.max(1);
+        let colors = if palette.is_empty() { self.color_table() } else { palette.to_vec() };
+        let (width, height) = (self.width * size, self.height * size);
+        let states: Vec<u8> = self.color_indices().collect();
+        let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" \
+            viewBox=\"0 0 {0} {1}\" shape-rendering=\"crispEdges\">\n", width, height);
+        let _ = writeln!(svg, "<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>", width, height, css_color(colors[0]));
+        for state in 1..=states.iter().cloned().max().unwrap_or(0) {
+            let color = colors[(state as usize).min(colors.len() - 1)];
+            let _ = writeln!(svg, "<g fill=\"{}\">", css_color(color));
+            for (row, cells) in states.chunks(self.width.max(1) as usize).enumerate() {
+                let mut col = 0;
+                while col < cells.len() {
+                    let run = cells[col..].iter().take_while(|&&cell| cell == state).count();
+                    if run > 0 {
+                        let _ = writeln!(svg, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+                            col as u32 * size, row as u32 * size, run as u32 * size, size);
+                    }
+                    col += run.max(1);
+                }
+            }
+            svg.push_str("</g>\n");
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[cfg(feature = "png-export")]
+#[wasm_bindgen]
+impl Universe {
+    //PNG file of render_rgba(cell_size)
+    #[wasm_bindgen(js_name = to_png)]
+    pub fn to_png_js(&self, cell_size: u32) -> Result<Vec<u8>, Error> {
+        self.to_png(cell_size)
+    }
+}
+
+#[cfg(feature = "png-export")]
+impl Universe {
+    pub fn to_png(&self, cell_size: u32) -> Result<Vec<u8>, Error> {
+        let size = cell_size.max(1);
+        ::renderer::encode_png(self.width * size, self.height * size, &self.render_rgba(size))
+    }
+}
+
+#[cfg(feature = "gif-export")]
+#[wasm_bindgen]
+impl Universe {
+    //Animated GIF of the next generations ticks, one frame before the first tick and one after
+    //each, shown frame_delay_ms apart (rounded down to GIF's 10ms steps) and looping forever.
+    //The universe is left generations ticks later
+    #[wasm_bindgen(js_name = record_gif)]
+    pub fn record_gif_js(&mut self, generations: u32, cell_size: u32, frame_delay_ms: u32) -> Result<Vec<u8>, Error> {
+        self.record_gif(generations, cell_size, frame_delay_ms)
+    }
+}
+
+#[cfg(feature = "gif-export")]
+impl Universe {
+    pub fn record_gif(&mut self, generations: u32, cell_size: u32, frame_delay_ms: u32) -> Result<Vec<u8>, Error> {
+        use std::convert::TryFrom;
+        let size = cell_size.max(1);
+        let dimension = |cells: u32| u16::try_from(u64::from(cells) * u64::from(size)).map_err(|_| "GIF frames are at most 65535 pixels wide".to_string());
+        let (width, height) = (dimension(self.width)?, dimension(self.height)?);
+        //Pixels are colour indices, the GIF palette is the colour table
+        let colors = self.color_table();
+        let palette: Vec<u8> = colors.iter().take(256).flat_map(|&color| rgba(color)[..3].to_vec()).collect();
+        let indices: Vec<Vec<u8>> = (0..colors.len()).map(|state| vec![state as u8]).collect();
+        let delay = u16::try_from(frame_delay_ms / 10).unwrap_or(u16::MAX);
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = gif::Encoder::new(&mut bytes, width, height, &palette).map_err(|e| Error::Invalid(e.to_string()))?;
+            encoder.set_repeat(gif::Repeat::Infinite).map_err(|e| Error::Invalid(e.to_string()))?;
+            let mut pixels = Vec::new();
+            for frame in 0..=generations {
+                if frame > 0 {
+                    self.tick();
+                }
+                self.render_into(size, &indices, &mut pixels);
+                let mut frame = gif::Frame::from_indexed_pixels(width, height, &pixels[..], None);
+                frame.delay = delay;
+                encoder.write_frame(&frame).map_err(|e| Error::Invalid(e.to_string()))?;
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Cell::*;
+    use super::super::AgeTracking;
+
+    #[test]
+    fn should_render_cells_as_pixel_squares() {
+        let mut univ = Universe::with_cells(2, 1, vec![Dead; 2]);
+        univ.set_cell(0, 1, Alive);
+        let palette = univ.palette();
+        let (dead, alive) = (rgba(palette[0]), rgba(palette[1]));
+        let frame = univ.render_rgba(2);
+        assert_eq!(frame.len(), 4 * 2 * 4);
+        let line: Vec<u8> = [dead, dead, alive, alive].concat();
+        assert_eq!(frame, [&line[..], &line[..]].concat());
+        assert_eq!(univ.render_rgba(0), [dead, alive].concat());
+    }
+
+    #[test]
+    fn should_export_runs_of_cells_as_svg_rects() {
+        let mut univ = Universe::with_cells(3, 2, vec![Dead; 6]);
+        univ.set_cell(0, 0, Alive);
+        univ.set_cell(0, 1, Alive);
+        univ.set_cell(1, 2, Alive);
+        assert_eq!(univ.to_svg(10, &[0xffffff, 0x102030]), "\
+            <svg xmlns=\"http://www.w3.org/2000/svg\" width=\"30\" height=\"20\" viewBox=\"0 0 30 20\" shape-rendering=\"crispEdges\">\n\
+            <rect width=\"30\" height=\"20\" fill=\"#ffffff\"/>\n\
+            <g fill=\"#102030\">\n\
+            <rect x=\"0\" y=\"0\" width=\"20\" height=\"10\"/>\n\
+            <rect x=\"20\" y=\"10\" width=\"10\" height=\"10\"/>\n\
+            </g>\n\
+            </svg>\n");
+        assert!(univ.to_svg(1, &[]).contains(&css_color(univ.palette()[1])));
+    }
+
+    #[test]
+    fn should_colour_cells_from_palette_and_age() {
+        let mut univ = Universe::with_cells(3, 1, vec![Dead; 3]);
+        univ.set_cell(0, 1, Alive);
+        let mut palette = Palette::new(0x000000, 0xffffff);
+        palette.add_age_stop(1, 0xff0000);
+        univ.set_palette(&palette);
+        assert_eq!(univ.color_table()[..2], [0x000000, 0xffffff]);
+        assert_eq!(univ.color_indices_js(), vec![0, 1, 0]);
+        assert_eq!(univ.render_rgba(1), [[0, 0, 0, 255], [255, 255, 255, 255], [0, 0, 0, 255]].concat());
+
+        univ.set_age_tracking(AgeTracking::Alive);
+        univ.ages[1] = 1;
+        let red = univ.state_count();
+        assert_eq!(univ.color_indices_js(), vec![0, red, 0]);
+        assert_eq!(univ.render_rgba(1)[4..8], [255, 0, 0, 255]);
+        univ.reset_palette();
+        assert_eq!(univ.color_indices_js(), vec![0, 1, 0]);
+    }
+
+    #[cfg(feature = "png-export")]
+    #[test]
+    fn should_encode_frame_as_png() {
+        let mut univ = Universe::with_cells(3, 2, vec![Dead; 6]);
+        univ.set_cell(1, 2, Alive);
+        let bytes = univ.to_png(2).unwrap();
+        let mut reader = png::Decoder::new(&bytes[..]).read_info().unwrap();
+        let mut frame = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut frame).unwrap();
+        assert_eq!((info.width, info.height), (6, 4));
+        assert_eq!(frame, univ.render_rgba(2));
+    }
+
+    #[cfg(feature = "gif-export")]
+    #[test]
+    fn should_record_generations_as_gif_frames() {
+        let mut univ = Universe::with_cells(5, 5, vec![Dead; 25]);
+        univ.set_cells(&[(2, 1), (2, 2), (2, 3)]).unwrap();
+        let bytes = univ.record_gif(2, 3, 100).unwrap();
+        assert_eq!(univ.generation(), 2);
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(&bytes[..]).unwrap();
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!((frame.width, frame.height, frame.delay), (15, 15, 10));
+            frames.push(frame.buffer.to_vec());
+        }
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0], frames[2]);
+        assert_ne!(frames[0], frames[1]);
+        assert_eq!(frames[0][7 * 15 + 3..7 * 15 + 12], [1; 9]);
+        assert!(univ.record_gif(0, 20000, 0).is_err());
+    }
+}
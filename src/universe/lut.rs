@@ -0,0 +1,35 @@
+use rule::Rule;
+use super::Cell;
+
+//Next state of the middle cell of every 3x3 window of a two state rule. A window is 9 bits read
+//column by column, the left column in the top bits and each column top to bottom, so moving one
+//cell along a row shifts a single column in
+pub struct WindowTable {
+    next: [u8; 512],
+}
+
+impl WindowTable {
+    pub fn new(rule: &Rule) -> WindowTable {
+        let mut next = [0u8; 512];
+        for (window, next) in next.iter_mut().enumerate() {
+            let bit = |n: usize| (window >> n & 1) as u8;
+            //Moore neighbours in reading order: NW N NE, W E, SW S SE
+            let config = [8, 5, 2, 7, 1, 6, 3, 0].iter().fold(0, |config, &n| config << 1 | bit(n));
+            let cell = if bit(4) == 1 { Cell::Alive } else { Cell::Dead };
+            *next = rule.next_from_moore(cell, config) as u8;
+        }
+        WindowTable { next }
+    }
+
+    //Same as simd::next_row, columns before start are left alone
+    pub fn next_row(&self, above: &[u8], row: &[u8], below: &[u8], start: usize, out: &mut [u8]) {
+        let padded = out.len() + 2;
+        assert!(above.len() >= padded && row.len() >= padded && below.len() >= padded && start <= out.len());
+        let column = |col: usize| (at!(above, col) << 2 | at!(row, col) << 1 | at!(below, col)) as usize;
+        let mut window = column(start) << 3 | column(start + 1);
+        for (col, next) in out.iter_mut().enumerate().skip(start) {
+            window = (window << 3 & 0x1ff) | column(col + 2);
+            *next = at!(self.next, window);
+        }
+    }
+}
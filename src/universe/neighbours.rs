@@ -0,0 +1,48 @@
+use rule::MOORE_OFFSETS;
+use super::Boundary;
+
+//Where the neighbours of a cell are, worked out once per board size and boundary. Interior cells
+//find their eight Moore neighbours at fixed index deltas; on the border the row and column one
+//step past each edge are looked up instead of resolved. Twisted boundaries can't be split into
+//rows and columns, for those only the deltas are used
+#[derive(Debug, Clone)]
+pub struct NeighbourTable {
+    deltas: [isize; 8],
+    //Row or column seen at -1, 0, .., size, None past a dead edge
+    rows: Vec<Option<usize>>,
+    columns: Vec<Option<usize>>,
+    width: usize,
+}
+
+impl NeighbourTable {
+    pub fn new(width: u32, height: u32, boundary: Boundary) -> NeighbourTable {
+        let mut deltas = [0; 8];
+        for (delta, &(rows, columns)) in deltas.iter_mut().zip(MOORE_OFFSETS.iter()) {
+            *delta = rows as isize * width as isize + columns as isize;
+        }
+        let line = |size: u32| (-1..=i64::from(size)).map(|x| boundary.resolve(x, size)).collect();
+        NeighbourTable { deltas, rows: line(height), columns: line(width), width: width as usize }
+    }
+
+    //Index deltas of the Moore neighbours in MOORE_OFFSETS order, for cells off the border
+    pub fn deltas(&self) -> &[isize; 8] {
+        &self.deltas
+    }
+
+    //Row seen at row (-1 to height), see Boundary::resolve
+    pub fn row(&self, row: i64) -> Option<usize> {
+        self.rows[(row + 1) as usize]
+    }
+
+    //Columns seen from -1 to width
+    pub fn columns(&self) -> &[Option<usize>] {
+        &self.columns
+    }
+
+    //Index of the cell at most one row and column away from (row, column) on an untwisted board
+    pub fn index(&self, row: u32, column: u32, rows: i32, columns: i32) -> Option<usize> {
+        let row = self.rows[(i64::from(row) + 1 + i64::from(rows)) as usize]?;
+        let column = self.columns[(i64::from(column) + 1 + i64::from(columns)) as usize]?;
+        Some(row * self.width + column)
+    }
+}
@@ -0,0 +1,129 @@
+use wasm_bindgen::prelude::*;
+use error::Error;
+use super::{Cell, Universe};
+
+//How a pasted cell combines with the cell already on the board
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasteMode {
+    Overwrite,
+    Or,
+    And,
+    Xor,
+}
+
+impl PasteMode {
+    fn blend(self, below: Cell, above: Cell) -> Cell {
+        let (below, above) = (below == Cell::Alive, above == Cell::Alive);
+        let alive = match self {
+            PasteMode::Overwrite => above,
+            PasteMode::Or => below || above,
+            PasteMode::And => below && above,
+            PasteMode::Xor => below != above,
+        };
+        if alive { Cell::Alive } else { Cell::Dead }
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    //New independent universe holding the height x width rectangle whose top-left is (row, column).
+    //The rectangle wraps around the board edges and can't be empty
+    pub fn copy_region(&self, row: u32, column: u32, height: u32, width: u32) -> Result<Universe, Error> {
+        Universe::check_size(width, height)?;
+        let (cells, states) = self.gather(width, height, |r, c| {
+            Some(((row + r) % self.height, (column + c) % self.width))
+        });
+        let mut copy = Universe::with_cells(width, height, cells);
+        copy.copy_settings(self);
+        copy.states = states;
+        Ok(copy)
+    }
+
+    //Composite other into this universe with its top-left at (row, column), wrapping around the edges
+    pub fn paste(&mut self, other: &Universe, row: u32, column: u32, mode: PasteMode) {
+        for (r, c) in iproduct!(0..other.height, 0..other.width) {
+            let idx = self.wrapped_index(row + r, column + c);
+            let state = mode.blend(self.cells.get(idx), other.cells.get(other.get_index(r, c)));
+            self.write_cell(idx, state);
+        }
+    }
+
+    //Packed [row0, col0, row1, col1, ...] of the cells where the two universes disagree
+    #[wasm_bindgen(js_name = diff)]
+    pub fn diff_js(&self, other: &Universe) -> Result<Vec<u32>, Error> {
+        self.diff(other)
+            .map(|coords| coords.into_iter().flat_map(|(r, c)| vec![r, c]).collect())
+    }
+}
+
+impl Universe {
+    //Coordinates where the two universes disagree, both must have the same dimensions
+    pub fn diff(&self, other: &Universe) -> Result<Vec<(u32, u32)>, Error> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(Error::Invalid(format!("cannot diff a {}x{} universe against a {}x{} one",
+                self.width, self.height, other.width, other.height)));
+        }
+        let mut changed = Vec::new();
+        self.cells.changed(&other.cells, &mut changed);
+        Ok(changed.into_iter().map(|idx| (idx / self.width, idx % self.width)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PasteMode;
+    use super::super::Universe;
+    use super::super::Cell::*;
+
+    #[test]
+    fn should_copy_region_with_wrap() {
+        let univ = Universe::with_cells(3, 3, vec![
+            Alive, Dead,  Dead,
+            Dead,  Alive, Dead,
+            Dead,  Dead,  Alive,
+        ]);
+        let copy = univ.copy_region(2, 1, 2, 3).unwrap();
+        assert_eq!((copy.width(), copy.height()), (3, 2));
+        assert_eq!(copy.get_cells(), &[
+            Dead, Alive, Dead,
+            Dead, Dead,  Alive,
+        ]);
+        assert_eq!(copy.population(), 2);
+        assert_eq!(copy.generation(), 0);
+    }
+
+    #[test]
+    fn should_paste_with_blend_modes() {
+        let base = vec![
+            Alive, Alive, Dead,
+            Dead,  Dead,  Dead,
+        ];
+        let stamp = Universe::with_cells(2, 1, vec![Alive, Dead]);
+        let pasted = |mode| {
+            let mut univ = Universe::with_cells(3, 2, base.clone());
+            univ.paste(&stamp, 0, 1, mode);
+            (univ.get_cells()[..3].to_vec(), univ.population())
+        };
+        assert_eq!(pasted(PasteMode::Overwrite), (vec![Alive, Alive, Dead], 2));
+        assert_eq!(pasted(PasteMode::Or), (vec![Alive, Alive, Dead], 2));
+        assert_eq!(pasted(PasteMode::And), (vec![Alive, Alive, Dead], 2));
+        assert_eq!(pasted(PasteMode::Xor), (vec![Alive, Dead, Dead], 1));
+
+        let mut wrapped = Universe::with_cells(3, 2, base.clone());
+        wrapped.paste(&stamp, 1, 2, PasteMode::Overwrite);
+        assert_eq!(wrapped.get_cells()[3..], [Dead, Dead, Alive]);
+        assert_eq!(wrapped.population(), 3);
+    }
+
+    #[test]
+    fn should_diff_equally_sized_universes() {
+        let mut left = Universe::with_cells(3, 2, vec![Dead; 6]);
+        let right = Universe::with_cells(3, 2, vec![Dead; 6]);
+        assert_eq!(left.diff(&right), Ok(vec![]));
+        left.set_cell(0, 2, Alive);
+        left.set_cell(1, 1, Alive);
+        assert_eq!(left.diff(&right), Ok(vec![(0, 2), (1, 1)]));
+        assert!(left.diff(&Universe::with_cells(2, 3, vec![Dead; 6])).is_err());
+    }
+}
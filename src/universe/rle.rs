@@ -0,0 +1,147 @@
+use wasm_bindgen::prelude::*;
+use formats::PatternInfo;
+use mode::Mode;
+use super::Universe;
+
+//Golly wraps RLE lines at 70 characters
+const LINE_LENGTH: usize = 70;
+
+#[wasm_bindgen]
+impl Universe {
+    //Run length encoded pattern of the live cells, cropped to their bounding box, with the
+    //x = .., y = .., rule = .. header Golly and LifeViewer expect
+    pub fn to_rle(&self) -> String {
+        match self.bounding_box() {
+            Some((r0, c0, r1, c1)) => self.region_to_rle(r0, c0, r1 - r0 + 1, c1 - c0 + 1),
+            None => self.region_to_rle(0, 0, 0, 0),
+        }
+    }
+
+    //to_rle with #N, #O and #C lines for the name, author and comments of info in front. The
+    //rule in the header is always the universe's own
+    pub fn to_rle_with_info(&self, info: &PatternInfo) -> String {
+        let mut rle = String::new();
+        if let Some(ref name) = info.name {
+            rle.push_str(&format!("#N {}\n", name));
+        }
+        if let Some(ref author) = info.author {
+            rle.push_str(&format!("#O {}\n", author));
+        }
+        for line in &info.comments {
+            rle.push_str(&format!("#C {}\n", line));
+        }
+        rle + &self.to_rle()
+    }
+
+    //RLE of the height x width rectangle whose top-left is (row, column), wrapping around the
+    //board edges like copy_region
+    pub fn region_to_rle(&self, row: u32, column: u32, height: u32, width: u32) -> String {
+        let states: Vec<u8> = iproduct!(0..height, 0..width)
+            .map(|(r, c)| self.cell_state((row + r) % self.height, (column + c) % self.width))
+            .collect();
+        let mut rle = format!("x = {}, y = {}", width, height);
+        if let Some(rule) = self.rle_rule() {
+            rle.push_str(", rule = ");
+            rle.push_str(&rule);
+        }
+        rle.push('\n');
+        rle.push_str(&encode_rle(&states, width as usize, self.state_count() > 2));
+        rle
+    }
+}
+
+impl Universe {
+    //Rule the way Golly names it, None for automata it has no name for
+    fn rle_rule(&self) -> Option<String> {
+        match self.mode {
+            Mode::Life => Some(self.rule.to_string()),
+            Mode::LargerThanLife => Some(self.ltl_rule.to_string()),
+            Mode::Elementary => Some(format!("W{}", self.elementary_rule)),
+            Mode::Wireworld => Some("WireWorld".to_string()),
+            Mode::Immigration => Some("Immigration".to_string()),
+            Mode::QuadLife => Some("QuadLife".to_string()),
+            Mode::Cyclic | Mode::Margolus => None,
+        }
+    }
+}
+
+//RLE symbol of a cell state: b and o for two state patterns, otherwise . for 0 and A to X with
+//a p to y prefix for every further 24 states
+fn rle_symbol(state: u8, multi_state: bool) -> String {
+    match (state, multi_state) {
+        (0, false) => "b".to_string(),
+        (_, false) => "o".to_string(),
+        (0, true) => ".".to_string(),
+        (_, true) => {
+            let (prefix, letter) = ((state - 1) / 24, (b'A' + (state - 1) % 24) as char);
+            match prefix {
+                0 => letter.to_string(),
+                _ => format!("{}{}", (b'o' + prefix) as char, letter),
+            }
+        }
+    }
+}
+
+//Pattern body of states row by row, width cells each. Dead cells ending a row and empty rows
+//at the end are left out, runs of row ends are merged into one count
+fn encode_rle(states: &[u8], width: usize, multi_state: bool) -> String {
+    let mut tokens = Vec::new();
+    let mut row_ends = 0;
+    for row in states.chunks(width.max(1)) {
+        let used = row.iter().rposition(|&state| state != 0).map_or(0, |last| last + 1);
+        if used > 0 && row_ends > 0 {
+            tokens.push(run(row_ends, "$"));
+            row_ends = 0;
+        }
+        let mut col = 0;
+        while col < used {
+            let length = row[col..used].iter().take_while(|&&state| state == row[col]).count();
+            tokens.push(run(length, &rle_symbol(row[col], multi_state)));
+            col += length;
+        }
+        row_ends += 1;
+    }
+    tokens.push("!".to_string());
+
+    let mut body = String::new();
+    let mut line = 0;
+    for token in tokens {
+        if line + token.len() > LINE_LENGTH {
+            body.push('\n');
+            line = 0;
+        }
+        line += token.len();
+        body.push_str(&token);
+    }
+    body.push('\n');
+    body
+}
+
+fn run(length: usize, symbol: &str) -> String {
+    if length == 1 { symbol.to_string() } else { format!("{}{}", length, symbol) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Cell::*;
+
+    #[test]
+    fn should_export_cropped_rle() {
+        let mut univ = Universe::with_cells(8, 8, vec![Dead; 64]);
+        //Glider
+        univ.set_cells(&[(2, 3), (3, 4), (4, 2), (4, 3), (4, 4)]).unwrap();
+        assert_eq!(univ.to_rle(), "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+        assert_eq!(univ.region_to_rle(1, 2, 5, 4), "x = 4, y = 5, rule = B3/S23\n$bo$2bo$3o!\n");
+        univ.clear();
+        assert_eq!(univ.to_rle(), "x = 0, y = 0, rule = B3/S23\n!\n");
+    }
+
+    #[test]
+    fn should_encode_states_and_wrap_lines() {
+        assert_eq!(encode_rle(&[0, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 26, 0, 0, 0], 5, true), ".AB2$.pB!\n");
+        let row: Vec<u8> = (0..80).map(|n| (n % 2) as u8).collect();
+        let body = encode_rle(&row, 80, false);
+        assert_eq!(body.lines().map(str::len).collect::<Vec<_>>(), vec![70, 11]);
+    }
+}
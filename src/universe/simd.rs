@@ -0,0 +1,33 @@
+//Row kernel for two state totalistic Moore rules on the wasm simd128 build. Cells are one byte
+//each (0 or 1) so 16 neighbourhoods are counted per instruction
+
+//Start of the next generation of one row. above, row and below hold the cells of the row and the
+//rows around it with one padding cell on each side, out gets one byte per cell. birth and
+//survival map the neighbour counts 0 to 8 to 0 or 1, the rest is never read. Returns the first
+//column left for lut::WindowTable::next_row, 0 on builds without simd128
+pub fn next_row(above: &[u8], row: &[u8], below: &[u8], birth: &[u8; 16], survival: &[u8; 16], out: &mut [u8]) -> usize {
+    next_row_simd(above, row, below, birth, survival, out)
+}
+
+#[cfg(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))]
+fn next_row_simd(above: &[u8], row: &[u8], below: &[u8], birth: &[u8; 16], survival: &[u8; 16], out: &mut [u8]) -> usize {
+    use std::arch::wasm32::*;
+    //Unaligned 16 byte loads, bounds checked by the slicing
+    let load = |line: &[u8], at: usize| unsafe { v128_load(line[at..at + 16].as_ptr() as *const v128) };
+    let (birth, survival) = (load(birth, 0), load(survival, 0));
+    let mut col = 0;
+    while col + 16 <= out.len() {
+        let count = u8x16_add(u8x16_add(u8x16_add(load(above, col), load(above, col + 1)), u8x16_add(load(above, col + 2), load(row, col))),
+            u8x16_add(u8x16_add(load(row, col + 2), load(below, col)), u8x16_add(load(below, col + 1), load(below, col + 2))));
+        let cell = load(row, col + 1);
+        let next = v128_or(v128_and(u8x16_swizzle(survival, count), cell), v128_andnot(u8x16_swizzle(birth, count), cell));
+        unsafe { v128_store(out[col..col + 16].as_mut_ptr() as *mut v128, next) };
+        col += 16;
+    }
+    col
+}
+
+#[cfg(not(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128")))]
+fn next_row_simd(_: &[u8], _: &[u8], _: &[u8], _: &[u8; 16], _: &[u8; 16], _: &mut [u8]) -> usize {
+    0
+}
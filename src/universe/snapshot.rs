@@ -0,0 +1,325 @@
+use wasm_bindgen::prelude::*;
+use std::convert::TryFrom;
+use std::iter;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use compress::maybe_gunzip;
+use error::Error;
+use mode::Mode;
+use super::Universe;
+
+//Start of every binary snapshot, followed by the format version
+const MAGIC: &[u8; 4] = b"GOLS";
+const VERSION: u8 = 1;
+
+//What a universe is saved as: its size, B/S rule, generation and one state per cell row by row.
+//Only Life mode universes, the other modes keep rules a snapshot has no room for
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    width: u32,
+    height: u32,
+    rule: String,
+    generation: u32,
+    cells: Vec<u8>,
+}
+
+impl Snapshot {
+    fn new(universe: &Universe) -> Result<Snapshot, Error> {
+        if universe.mode != Mode::Life {
+            return Err(Error::Invalid(format!("{:?} mode universes can't be saved", universe.mode)));
+        }
+        Ok(Snapshot { width: universe.width, height: universe.height, rule: universe.rule.to_string(),
+            generation: universe.generation, cells: universe.state_bytes().collect() })
+    }
+
+    fn into_universe(self) -> Result<Universe, Error> {
+        Universe::check_size(self.width, self.height)?;
+        let cell_count = u64::from(self.width) * u64::from(self.height);
+        if self.cells.len() as u64 != cell_count {
+            return Err(Error::Invalid(format!("expected {} cells for a {}x{} universe but got {}", cell_count, self.width, self.height, self.cells.len())));
+        }
+        let mut universe = Universe::empty(self.width, self.height);
+        universe.set_rule(self.rule.parse()?);
+        for (idx, &state) in self.cells.iter().enumerate().filter(|(_, &state)| state != 0) {
+            universe.write_state(idx, state);
+        }
+        universe.generation = self.generation;
+        Ok(universe)
+    }
+
+    //MAGIC, VERSION, then width, height and generation as little endian u32s, the rule's length
+    //as a u16 and its text, the bits per cell (1, 2, 4 or 8, enough for the highest state) and
+    //the cells packed from the lowest bit of each byte up
+    fn to_bytes(&self) -> Vec<u8> {
+        let bits = cell_bits(self.cells.iter().cloned().max().unwrap_or(0));
+        let mut bytes = Vec::with_capacity(20 + self.rule.len() + self.cells.len() * bits / 8);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        for value in &[self.width, self.height, self.generation] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&(self.rule.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(self.rule.as_bytes());
+        bytes.push(bits as u8);
+        for chunk in self.cells.chunks(8 / bits) {
+            bytes.push(chunk.iter().enumerate().fold(0, |byte, (n, &state)| byte | state << (n * bits)));
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Snapshot, Error> {
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(Error::Invalid("not a universe snapshot".to_string()));
+        }
+        match reader.take(1)?[0] {
+            VERSION => {}
+            version => return Err(Error::Invalid(format!("unsupported snapshot version {}", version))),
+        }
+        let (width, height, generation) = (reader.u32()?, reader.u32()?, reader.u32()?);
+        let rule_length = u16::from_le_bytes([reader.take(1)?[0], reader.take(1)?[0]]) as usize;
+        let rule = String::from_utf8(reader.take(rule_length)?.to_vec()).map_err(|_| Error::Invalid("snapshot rule isn't UTF-8".to_string()))?;
+        let bits = reader.take(1)?[0] as usize;
+        if ![1, 2, 4, 8].contains(&bits) {
+            return Err(Error::Invalid(format!("unsupported {} bits per cell", bits)));
+        }
+        let cell_count = usize::try_from(u64::from(width) * u64::from(height)).map_err(|_| Error::Invalid("snapshot too big".to_string()))?;
+        let packed = reader.take(cell_count.div_ceil(8 / bits))?;
+        let mask = ((1u16 << bits) - 1) as u8;
+        let cells = (0..cell_count)
+            .map(|idx| packed[idx * bits / 8] >> (idx * bits % 8) & mask)
+            .collect();
+        Ok(Snapshot { width, height, rule, generation, cells })
+    }
+}
+
+//Fewest bits out of 1, 2, 4 and 8 that hold every state up to max
+fn cell_bits(max: u8) -> usize {
+    [1, 2, 4].iter().cloned().find(|&bits| u16::from(max) < 1 << bits).unwrap_or(8)
+}
+
+//PackBits: a byte n up to 127 is followed by n + 1 bytes copied as they are, one from 129 on by a
+//byte repeated 257 - n times. Long runs of dead cells shrink to two bytes per 128
+fn pack_bits(bytes: &[u8]) -> Vec<u8> {
+    let (mut packed, mut idx) = (Vec::new(), 0);
+    while idx < bytes.len() {
+        let run = bytes[idx..].iter().take(128).take_while(|&&byte| byte == bytes[idx]).count();
+        if run > 1 {
+            packed.push((257 - run) as u8);
+            packed.push(bytes[idx]);
+            idx += run;
+            continue;
+        }
+        let start = idx;
+        while idx < bytes.len() && idx - start < 128 && bytes.get(idx + 1) != Some(&bytes[idx]) {
+            idx += 1;
+        }
+        packed.push((idx - start - 1) as u8);
+        packed.extend_from_slice(&bytes[start..idx]);
+    }
+    packed
+}
+
+fn unpack_bits(packed: &[u8]) -> Result<Vec<u8>, Error> {
+    let (mut bytes, mut idx) = (Vec::new(), 0);
+    while let Some(&n) = packed.get(idx) {
+        //128 is a one byte no-op, even as the last byte
+        if n == 128 {
+            idx += 1;
+            continue;
+        }
+        let end = idx + 2 + if n < 128 { n as usize } else { 0 };
+        let data = packed.get(idx + 1..end).ok_or_else(|| Error::Invalid("share string ends early".to_string()))?;
+        if n < 128 {
+            bytes.extend_from_slice(data);
+        } else {
+            bytes.extend(iter::repeat_n(data[0], 257 - n as usize));
+        }
+        idx = end;
+    }
+    Ok(bytes)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], Error> {
+        if self.bytes.len() < count {
+            return Err(Error::Invalid("snapshot ends early".to_string()));
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+impl Serialize for Universe {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Snapshot::new(self).map_err(S::Error::custom)?.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Universe {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Universe, D::Error> {
+        Snapshot::deserialize(deserializer)?.into_universe().map_err(D::Error::custom)
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    //JSON save of the size, rule, generation and cells, see Snapshot
+    #[wasm_bindgen(js_name = to_json)]
+    pub fn to_json_js(&self) -> Result<String, Error> {
+        self.to_json()
+    }
+
+    pub fn from_json(json: &str) -> Result<Universe, Error> {
+        serde_json::from_str(json).map_err(|e| Error::Parse { format: "JSON", line: e.line(), message: e.to_string() })
+    }
+
+    //The same as a versioned binary snapshot with the cells bit packed, a Uint8Array small enough
+    //for postMessage and IndexedDB (32KB for a two state 512x512 board)
+    #[wasm_bindgen(js_name = to_bytes)]
+    pub fn to_bytes_js(&self) -> Result<Vec<u8>, Error> {
+        self.to_bytes()
+    }
+
+    //to_bytes gzip compressed, from_bytes reads both. Mostly empty boards shrink a lot further
+    #[cfg(feature = "compress")]
+    #[wasm_bindgen(js_name = to_compressed_bytes)]
+    pub fn to_compressed_bytes_js(&self) -> Result<Vec<u8>, Error> {
+        self.to_compressed_bytes()
+    }
+
+    #[wasm_bindgen(js_name = from_bytes)]
+    pub fn from_bytes_js(bytes: &[u8]) -> Result<Universe, Error> {
+        Universe::from_bytes(bytes)
+    }
+
+    //The binary snapshot run length compressed and base64url encoded, short enough for the hash of
+    //a share link unless the board is big and busy
+    #[wasm_bindgen(js_name = to_share_string)]
+    pub fn to_share_string_js(&self) -> Result<String, Error> {
+        self.to_share_string()
+    }
+
+    #[wasm_bindgen(js_name = from_share_string)]
+    pub fn from_share_string_js(text: &str) -> Result<Universe, Error> {
+        Universe::from_share_string(text)
+    }
+}
+
+impl Universe {
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string(self).map_err(|e| Error::Invalid(e.to_string()))
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        Snapshot::new(self).map(|snapshot| snapshot.to_bytes())
+    }
+
+    //Takes gzip compressed snapshots too, see compress
+    pub fn from_bytes(bytes: &[u8]) -> Result<Universe, Error> {
+        Snapshot::from_bytes(&maybe_gunzip(bytes)?)?.into_universe()
+    }
+
+    #[cfg(feature = "compress")]
+    pub fn to_compressed_bytes(&self) -> Result<Vec<u8>, Error> {
+        self.to_bytes().map(|bytes| ::compress::gzip(&bytes))
+    }
+
+    pub fn to_share_string(&self) -> Result<String, Error> {
+        self.to_bytes().map(|bytes| URL_SAFE_NO_PAD.encode(pack_bits(&bytes)))
+    }
+
+    //Surrounding whitespace and a leading # (straight from location.hash) are ignored
+    pub fn from_share_string(text: &str) -> Result<Universe, Error> {
+        let text = text.trim();
+        let packed = URL_SAFE_NO_PAD.decode(text.strip_prefix('#').unwrap_or(text)).map_err(|e| Error::Invalid(e.to_string()))?;
+        Universe::from_bytes(&unpack_bits(&packed)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rule::Rule;
+
+    fn generations() -> Universe {
+        let mut univ = Universe::empty(4, 3);
+        univ.set_rule(Rule::generations(&[2], &[], 3));
+        univ.set_cells(&[(0, 1), (2, 3)]).unwrap();
+        univ.set_cell_state(1, 1, 2);
+        univ.generation = 7;
+        univ
+    }
+
+    fn assert_same(copy: &Universe, univ: &Universe) {
+        assert_eq!((copy.width(), copy.height(), copy.generation(), copy.rule()), (univ.width, univ.height, univ.generation, univ.rule));
+        assert_eq!(copy.state_bytes().collect::<Vec<_>>(), univ.state_bytes().collect::<Vec<_>>());
+        assert_eq!(copy.population(), univ.population());
+    }
+
+    #[test]
+    fn should_round_trip_json() {
+        let mut univ = generations();
+        let json = univ.to_json().unwrap();
+        assert_eq!(json, r#"{"width":4,"height":3,"rule":"B2/S/C3","generation":7,"cells":[0,1,0,0,0,2,0,0,0,0,0,1]}"#);
+        assert_same(&serde_json::from_str(&json).unwrap(), &univ);
+
+        assert!(serde_json::from_str::<Universe>(r#"{"width":2,"height":2,"rule":"B3/S23","generation":0,"cells":[1]}"#).is_err());
+        univ.set_mode(Mode::Wireworld);
+        assert!(univ.to_json().is_err());
+    }
+
+    #[test]
+    fn should_round_trip_bytes() {
+        let univ = generations();
+        let bytes = univ.to_bytes().unwrap();
+        assert_eq!(&bytes[..5], b"GOLS\x01");
+        assert_eq!(&bytes[bytes.len() - 4..], &[2, 0b0100, 0b1000, 0b0100_0000]);
+        assert_same(&Universe::from_bytes(&bytes).unwrap(), &univ);
+        assert!(Universe::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        let mut big = Universe::empty(512, 512);
+        big.set_cells(&[(0, 0), (511, 511)]).unwrap();
+        let bytes = big.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 20 + "B3/S23".len() + 512 * 512 / 8);
+        assert_same(&Universe::from_bytes(&bytes).unwrap(), &big);
+        #[cfg(feature = "compress")]
+        {
+            let compressed = big.to_compressed_bytes().unwrap();
+            assert!(compressed.len() < 200);
+            assert_same(&Universe::from_bytes(&compressed).unwrap(), &big);
+        }
+    }
+
+    #[test]
+    fn should_share_short_strings() {
+        let bytes = [0, 0, 0, 1, 2, 3, 3, 4];
+        assert_eq!(pack_bits(&bytes), vec![254, 0, 1, 1, 2, 255, 3, 0, 4]);
+        assert_eq!(unpack_bits(&pack_bits(&bytes)), Ok(bytes.to_vec()));
+        let zeros = vec![0; 300];
+        assert_eq!(unpack_bits(&pack_bits(&zeros)), Ok(zeros));
+        assert!(unpack_bits(&[3, 1]).is_err());
+        assert_eq!(unpack_bits(&[128, 1, 5, 6, 128]), Ok(vec![5, 6]));
+
+        let mut univ = Universe::empty(128, 128);
+        univ.set_cells(&[(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]).unwrap();
+        let text = univ.to_share_string().unwrap();
+        assert!(text.len() < 100, "{}", text);
+        assert!(text.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_same(&Universe::from_share_string(&format!("#{}", text)).unwrap(), &univ);
+        assert!(Universe::from_share_string("#not base64!").is_err());
+    }
+}
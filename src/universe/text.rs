@@ -0,0 +1,236 @@
+use wasm_bindgen::prelude::*;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+use mode::Mode;
+use renderer::rgba;
+use error::Error;
+use super::{Cell, Universe};
+
+//Bit of the braille dot for the cell at (row, column) within a 4x2 block, columns are dots 1-2-3-7
+//and 4-5-6-8
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+//Characters render() and Display write: the mode's symbols, nothing between cells and a newline
+//after every row unless set otherwise
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glyphs {
+    //(dead, alive) in place of the symbols of states 0 and 1
+    chars: Option<(char, char)>,
+    row_separator: String,
+    column_spacer: String,
+}
+
+impl Default for Glyphs {
+    fn default() -> Glyphs {
+        Glyphs { chars: None, row_separator: "\n".to_string(), column_spacer: String::new() }
+    }
+}
+
+impl Glyphs {
+    pub fn symbol(&self, mode: Mode, state: u8) -> char {
+        match (self.chars, state) {
+            (Some((dead, _)), 0) => dead,
+            (Some((_, alive)), 1) => alive,
+            _ => mode.symbol(state),
+        }
+    }
+
+    //states row by row, width cells each
+    pub fn write_rows<W: Write>(&self, out: &mut W, mode: Mode, width: usize, states: &[u8]) -> fmt::Result {
+        for line in states.chunks(width.max(1)) {
+            for (col, &state) in line.iter().enumerate() {
+                if col > 0 {
+                    out.write_str(&self.column_spacer)?;
+                }
+                out.write_char(self.symbol(mode, state))?;
+            }
+            out.write_str(&self.row_separator)?;
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    //Write dead and alive cells as these characters in render(), e.g. '.' and 'O' for plain text.
+    //Other states keep the mode's symbols
+    pub fn set_render_chars(&mut self, dead: char, alive: char) {
+        self.glyphs.chars = Some((dead, alive));
+    }
+
+    //Back to the mode's symbols
+    pub fn reset_render_chars(&mut self) {
+        self.glyphs.chars = None;
+    }
+
+    //Written after every row, "\n" by default
+    pub fn set_row_separator(&mut self, separator: &str) {
+        self.glyphs.row_separator = separator.to_string();
+    }
+
+    //Written between the cells of a row, empty by default
+    pub fn set_column_spacer(&mut self, spacer: &str) {
+        self.glyphs.column_spacer = spacer.to_string();
+    }
+
+    //render() of the rows x columns cells from (row, column) only, each cell written scale times
+    //along its row and each row scale times. Cells past the edges follow the boundary, dead
+    //edges show as dead cells
+    pub fn render_region(&self, row: u32, column: u32, rows: u32, columns: u32, scale: u32) -> String {
+        let scale = scale.max(1) as usize;
+        let mut states = Vec::with_capacity(rows as usize * columns as usize * scale * scale);
+        for r in i64::from(row)..i64::from(row) + i64::from(rows) {
+            let line = (i64::from(column)..i64::from(column) + i64::from(columns))
+                .map(|c| match self.boundary.resolve_cell(r, c, self.height, self.width) {
+                    Some((r, c)) => self.cell_state(r as u32, c as u32),
+                    None => 0,
+                })
+                .flat_map(|state| ::std::iter::repeat_n(state, scale))
+                .collect::<Vec<u8>>();
+            for _ in 0..scale {
+                states.extend_from_slice(&line);
+            }
+        }
+        let mut text = String::new();
+        let _ = self.glyphs.write_rows(&mut text, self.mode, columns as usize * scale, &states);
+        text
+    }
+
+    //One braille character per 2x4 block of cells, a dot for every cell not in state 0. Lines end
+    //with a newline like render(), cells past the edges count as dead
+    pub fn render_braille(&self) -> String {
+        let states: Vec<u8> = self.state_bytes().collect();
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut text = String::new();
+        for top in (0..height).step_by(4) {
+            for left in (0..width).step_by(2) {
+                let dots = iproduct!(0..4, 0..2)
+                    .filter(|&(row, col)| top + row < height && left + col < width && states[(top + row) * width + left + col] != 0)
+                    .fold(0, |dots, (row, col)| dots | BRAILLE_DOTS[row][col]);
+                text.push(::std::char::from_u32(0x2800 + dots).unwrap_or(' '));
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    //Frame for 24-bit colour terminals: one upper half block per two rows of cells, the top cell
+    //as foreground and the bottom one as background, in the color_table() colours so palettes and
+    //age stops apply. Colours are only sent when they change and each line ends with a reset
+    pub fn render_ansi(&self) -> String {
+        let colors: Vec<[u8; 4]> = self.color_table().into_iter().map(rgba).collect();
+        let indices: Vec<u8> = self.color_indices().collect();
+        let (width, height) = (self.width as usize, self.height as usize);
+        //Past the bottom edge shows the dead colour
+        let color = |row: usize, col: usize| {
+            if row < height { colors[(indices[row * width + col] as usize).min(colors.len() - 1)] } else { colors[0] }
+        };
+        let mut text = String::new();
+        for top in (0..height).step_by(2) {
+            let mut last = None;
+            for col in 0..width {
+                let (fg, bg) = (color(top, col), color(top + 1, col));
+                if last != Some((fg, bg)) {
+                    let _ = write!(text, "\x1b[38;2;{};{};{};48;2;{};{};{}m", fg[0], fg[1], fg[2], bg[0], bg[1], bg[2]);
+                    last = Some((fg, bg));
+                }
+                text.push('\u{2580}');
+            }
+            text.push_str("\x1b[0m\n");
+        }
+        text
+    }
+}
+
+//Reads back what Display writes with the default glyphs: one line per row of ◻ and ◼, or . and O
+//(* too) as in .cells files. Surrounding whitespace and blank lines are skipped, so indented
+//literals in tests work, and every row has to be as long as the first
+impl FromStr for Universe {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Universe, Error> {
+        let error = |line, message| Error::Parse { format: "universe text", line, message };
+        let lines: Vec<(usize, &str)> = text.lines().map(str::trim).enumerate().filter(|(_, line)| !line.is_empty()).collect();
+        let rows = lines.iter()
+            .map(|&(number, line)| line.chars().map(|c| match c {
+                '◻' | '.' => Ok(Cell::Dead),
+                '◼' | 'O' | '*' => Ok(Cell::Alive),
+                _ => Err(error(number + 1, format!("unexpected {}", c))),
+            }).collect::<Result<Vec<_>, _>>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let width = rows.first().map_or(0, Vec::len);
+        if let Some(row) = rows.iter().position(|cells| cells.len() != width) {
+            return Err(error(lines[row].0 + 1, format!("{} cells but the first row has {}", rows[row].len(), width)));
+        }
+        let height = rows.len() as u32;
+        Universe::check_size(width as u32, height)?;
+        Ok(Universe::with_cells(width as u32, height, rows.concat()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Boundary;
+    use super::super::Cell::*;
+    use renderer::Palette;
+
+    #[test]
+    fn should_render_with_custom_glyphs() {
+        let mut univ = Universe::with_cells(3, 2, vec![Dead; 6]);
+        univ.set_cells(&[(0, 1), (1, 2)]).unwrap();
+        assert_eq!(univ.render(), "◻◼◻\n◻◻◼\n");
+        univ.set_render_chars('.', 'O');
+        univ.set_column_spacer(" ");
+        univ.set_row_separator("|");
+        assert_eq!(univ.render(), ". O .|. . O|");
+        univ.reset_render_chars();
+        assert_eq!(univ.to_string(), "◻ ◼ ◻|◻ ◻ ◼|");
+    }
+
+    #[test]
+    fn should_parse_rendered_text() {
+        let mut univ = Universe::with_cells(3, 2, vec![Dead; 6]);
+        univ.set_cells(&[(0, 1), (1, 2)]).unwrap();
+        assert_eq!(univ.to_string().parse::<Universe>().unwrap(), univ);
+        let glider: Universe = "
+            .O.
+            ..O
+            OOO
+        ".parse().unwrap();
+        assert_eq!((glider.width(), glider.height(), glider.population()), (3, 3, 5));
+        assert_eq!(glider.to_string(), "◻◼◻\n◻◻◼\n◼◼◼\n");
+        assert_eq!("O..\n\n.O".parse::<Universe>().unwrap_err().to_string(), "bad universe text at line 3: 2 cells but the first row has 3");
+        assert_eq!("Ox".parse::<Universe>().unwrap_err().to_string(), "bad universe text at line 1: unexpected x");
+    }
+
+    #[test]
+    fn should_render_zoomed_region() {
+        let mut univ = Universe::with_cells(4, 4, vec![Dead; 16]);
+        univ.set_cells(&[(1, 1), (3, 3)]).unwrap();
+        univ.set_render_chars('.', '#');
+        assert_eq!(univ.render_region(1, 1, 1, 2, 2), "##..\n##..\n");
+        assert_eq!(univ.render_region(3, 3, 2, 2, 1), "#.\n..\n");
+        univ.set_boundary(Boundary::Dead);
+        assert_eq!(univ.render_region(3, 3, 2, 2, 0), "#.\n..\n");
+        assert_eq!(univ.render_region(0, 0, 0, 3, 1), "");
+    }
+
+    #[test]
+    fn should_pack_blocks_into_braille() {
+        let mut univ = Universe::with_cells(3, 5, vec![Dead; 15]);
+        univ.set_cells(&[(0, 0), (3, 1), (1, 2), (4, 0)]).unwrap();
+        assert_eq!(univ.render_braille(), "\u{2881}\u{2802}\n\u{2801}\u{2800}\n");
+    }
+
+    #[test]
+    fn should_render_half_blocks_with_ansi_colours() {
+        let mut univ = Universe::with_cells(3, 3, vec![Dead; 9]);
+        univ.set_palette(&Palette::new(0x000000, 0xff8000));
+        univ.set_cells(&[(0, 0), (1, 0), (1, 2), (2, 2)]).unwrap();
+        let (both, neither, bottom) = ("\x1b[38;2;255;128;0;48;2;255;128;0m", "\x1b[38;2;0;0;0;48;2;0;0;0m", "\x1b[38;2;0;0;0;48;2;255;128;0m");
+        let top = "\x1b[38;2;255;128;0;48;2;0;0;0m";
+        assert_eq!(univ.render_ansi(), format!("{}\u{2580}{}\u{2580}{}\u{2580}\x1b[0m\n{}\u{2580}\u{2580}{}\u{2580}\x1b[0m\n",
+            both, neither, bottom, neither, top));
+    }
+}
@@ -0,0 +1,58 @@
+use wasm_bindgen::prelude::*;
+use error::Error;
+use super::Universe;
+
+#[wasm_bindgen]
+impl Universe {
+    pub fn trail_decay(&self) -> u8 {
+        self.trail_decay
+    }
+
+    //Keep a fading trail behind live cells: 255 while a cell is alive, then decay less every
+    //generation it stays dead. 0 turns trails off, larger values make them shorter
+    pub fn set_trail_decay(&mut self, decay: u8) {
+        self.trail_decay = decay;
+        self.reset_trails();
+    }
+
+    //Trail values as a Uint8Array of width * height entries, null while trails are off. Each
+    //tick updates it in place, resizing moves it like cells()
+    pub fn trails(&self) -> *const u8 {
+        if self.trails.is_empty() { ::std::ptr::null() } else { self.trails.as_ptr() }
+    }
+
+    //0 while trails are off
+    pub fn cell_trail(&self, row: u32, column: u32) -> Result<u8, Error> {
+        let idx = self.checked_index(row, column)?;
+        Ok(self.trails.get(idx).cloned().unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Cell::*;
+
+    #[test]
+    fn should_fade_trails_behind_dead_cells() {
+        let mut univ = Universe::with_cells(5, 5, vec![Dead; 25]);
+        let trail = |univ: &Universe, row, column| univ.cell_trail(row, column).unwrap();
+        univ.set_cells(&[(2, 1), (2, 2), (2, 3)]).unwrap();
+        assert!(univ.trails().is_null());
+        univ.set_trail_decay(100);
+        assert_eq!((trail(&univ, 2, 1), trail(&univ, 1, 2)), (255, 0));
+        univ.tick();
+        assert_eq!((trail(&univ, 2, 1), trail(&univ, 2, 2), trail(&univ, 1, 2)), (155, 255, 255));
+        univ.tick();
+        assert_eq!((trail(&univ, 2, 1), trail(&univ, 1, 2)), (255, 155));
+        univ.set_cell(0, 0, Alive);
+        univ.set_cell(2, 2, Dead);
+        assert_eq!((trail(&univ, 0, 0), trail(&univ, 2, 2)), (255, 0));
+        univ.tick();
+        univ.tick();
+        assert_eq!(trail(&univ, 0, 0), 55);
+        univ.set_trail_decay(0);
+        assert_eq!(trail(&univ, 0, 0), 0);
+        assert!(univ.cell_trail(0, 5).is_err());
+    }
+}
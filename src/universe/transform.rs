@@ -0,0 +1,115 @@
+use wasm_bindgen::prelude::*;
+use super::Universe;
+
+#[wasm_bindgen]
+impl Universe {
+    //Move every cell by (rows, columns), cells leaving one edge come back on the opposite one
+    pub fn shift(&mut self, rows: i32, columns: i32) {
+        let (height, width) = (i64::from(self.height), i64::from(self.width));
+        self.remap(self.width, self.height, |row, col| {
+            ((i64::from(row) - i64::from(rows)).rem_euclid(height) as u32,
+             (i64::from(col) - i64::from(columns)).rem_euclid(width) as u32)
+        });
+    }
+
+    //Rotate the whole board a quarter turn clockwise, width and height are swapped
+    pub fn rotate_cw(&mut self) {
+        let height = self.height;
+        self.remap(self.height, self.width, |row, col| (height - 1 - col, row));
+    }
+
+    //Rotate clockwise by quarter_turns * 90 degrees
+    pub fn rotate(&mut self, quarter_turns: u8) {
+        let (height, width) = (self.height, self.width);
+        match quarter_turns % 4 {
+            1 => self.rotate_cw(),
+            2 => self.remap(width, height, |row, col| (height - 1 - row, width - 1 - col)),
+            3 => self.remap(height, width, |row, col| (col, width - 1 - row)),
+            _ => {}
+        }
+    }
+
+    //Mirror left to right
+    pub fn flip_horizontal(&mut self) {
+        let width = self.width;
+        self.remap(self.width, self.height, |row, col| (row, width - 1 - col));
+    }
+
+    //Mirror top to bottom
+    pub fn flip_vertical(&mut self) {
+        let height = self.height;
+        self.remap(self.width, self.height, |row, col| (height - 1 - row, col));
+    }
+}
+
+impl Universe {
+    //Rebuild the grid as width x height, reading each new cell at (row, col) from source(row, col)
+    fn remap<F>(&mut self, width: u32, height: u32, source: F)
+        where F: Fn(u32, u32) -> (u32, u32) {
+        self.rebuild(width, height, |row, col| Some(source(row, col)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Universe;
+    use super::super::Cell::*;
+
+    #[test]
+    fn should_shift_with_wrap() {
+        let mut univ = Universe::with_cells(3, 2, vec![
+            Alive, Dead, Dead,
+            Dead,  Dead, Alive,
+        ]);
+        univ.shift(1, -1);
+        assert_eq!(univ.get_cells(), &[
+            Dead, Alive, Dead,
+            Dead, Dead,  Alive,
+        ]);
+        assert_eq!(univ.population(), 2);
+    }
+
+    #[test]
+    fn should_rotate_by_quarter_turns() {
+        let original = vec![
+            Alive, Alive, Dead,
+            Dead,  Dead,  Dead,
+        ];
+        let mut univ = Universe::with_cells(3, 2, original.clone());
+        univ.rotate_cw();
+        assert_eq!((univ.width(), univ.height()), (2, 3));
+        assert_eq!(univ.get_cells(), &[
+            Dead, Alive,
+            Dead, Alive,
+            Dead, Dead,
+        ]);
+
+        univ.rotate(3);
+        assert_eq!(univ.get_cells(), original.as_slice());
+        univ.rotate(2);
+        assert_eq!(univ.get_cells(), &[
+            Dead, Dead,  Dead,
+            Dead, Alive, Alive,
+        ]);
+        univ.rotate(6);
+        assert_eq!(univ.get_cells(), original.as_slice());
+    }
+
+    #[test]
+    fn should_flip_both_ways() {
+        let mut univ = Universe::with_cells(3, 2, vec![
+            Alive, Alive, Dead,
+            Dead,  Dead,  Dead,
+        ]);
+        univ.flip_horizontal();
+        assert_eq!(univ.get_cells(), &[
+            Dead, Alive, Alive,
+            Dead, Dead,  Dead,
+        ]);
+        univ.flip_vertical();
+        assert_eq!(univ.get_cells(), &[
+            Dead, Dead,  Dead,
+            Dead, Alive, Alive,
+        ]);
+    }
+}
@@ -0,0 +1,101 @@
+use wasm_bindgen::prelude::*;
+use super::Universe;
+
+//Part of the board shown on screen: rows x columns screen cells starting at board cell
+//(row, column), each screen cell covering a zoom x zoom square of board cells
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    row: i32,
+    column: i32,
+    rows: u32,
+    columns: u32,
+    zoom: u32,
+}
+
+#[wasm_bindgen]
+impl Viewport {
+    //zoom is clamped to at least 1
+    pub fn new(row: i32, column: i32, rows: u32, columns: u32, zoom: u32) -> Viewport {
+        Viewport { row, column, rows, columns, zoom: zoom.max(1) }
+    }
+
+    pub fn row(&self) -> i32 {
+        self.row
+    }
+
+    pub fn column(&self) -> i32 {
+        self.column
+    }
+
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    pub fn columns(&self) -> u32 {
+        self.columns
+    }
+
+    pub fn zoom(&self) -> u32 {
+        self.zoom
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = viewport;
+    }
+
+    //Move the viewport by whole board cells
+    pub fn pan_viewport(&mut self, rows: i32, columns: i32) {
+        self.viewport.row += rows;
+        self.viewport.column += columns;
+    }
+
+    //One byte per screen cell of the viewport, row by row: the highest cell state in the square
+    //it covers. Past the board edges cells follow the boundary, dead edges show as 0
+    pub fn render_viewport(&self) -> Vec<u8> {
+        let Viewport { row, column, rows, columns, zoom } = self.viewport;
+        let zoom = i64::from(zoom);
+        iproduct!(0..i64::from(rows), 0..i64::from(columns))
+            .map(|(r, c)| {
+                let (top, left) = (i64::from(row) + r * zoom, i64::from(column) + c * zoom);
+                iproduct!(top..top + zoom, left..left + zoom)
+                    .filter_map(|(r, c)| self.boundary.resolve_cell(r, c, self.height, self.width))
+                    .map(|(r, c)| self.cell_state(r as u32, c as u32))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Boundary;
+    use super::super::Cell::*;
+
+    #[test]
+    fn should_render_only_visible_cells() {
+        let mut univ = Universe::with_cells(8, 8, vec![Dead; 64]);
+        univ.set_cell(2, 3, Alive);
+        univ.set_viewport(Viewport::new(1, 2, 2, 3, 1));
+        assert_eq!(univ.render_viewport(), vec![0, 0, 0, 0, 1, 0]);
+
+        univ.set_viewport(Viewport::new(0, 0, 2, 2, 4));
+        assert_eq!(univ.render_viewport(), vec![1, 0, 0, 0]);
+
+        univ.set_viewport(Viewport::new(-1, 2, 1, 2, 1));
+        univ.pan_viewport(3, 1);
+        assert_eq!(univ.render_viewport(), vec![1, 0]);
+        univ.pan_viewport(-3, 0);
+        univ.set_boundary(Boundary::Dead);
+        assert_eq!(univ.render_viewport(), vec![0, 0]);
+    }
+}
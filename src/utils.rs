@@ -1,4 +1,6 @@
 use cfg_if::cfg_if;
+use std::collections::HashMap;
+use std::mem;
 
 cfg_if! {
     // When the `console_error_panic_hook` feature is enabled, we can call the
@@ -15,3 +17,56 @@ cfg_if! {
         pub fn set_panic_hook() {}
     }
 }
+
+//Heap bytes reserved by a vector
+pub fn vec_bytes<T>(vec: &Vec<T>) -> usize {
+    vec.capacity() * mem::size_of::<T>()
+}
+
+//Heap bytes reserved by a map's entries, the table's control bytes aside
+pub fn map_bytes<K, V>(map: &HashMap<K, V>) -> usize {
+    map.capacity() * (mem::size_of::<K>() + mem::size_of::<V>())
+}
+
+//Times the scope it lives in: console.time when created, console.timeEnd when dropped. The span
+//also goes to the browser's performance timeline as a measure between two marks
+pub struct Timer<'a> {
+    name: &'a str,
+}
+
+impl<'a> Timer<'a> {
+    pub fn new(name: &'a str) -> Timer<'a> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            web_sys::console::time_with_label(name);
+            if let Some(performance) = performance() {
+                let _ = performance.mark(&format!("{} start", name));
+            }
+        }
+        Timer { name }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name
+    }
+}
+
+impl<'a> Drop for Timer<'a> {
+    fn drop(&mut self) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            web_sys::console::time_end_with_label(self.name);
+            if let Some(performance) = performance() {
+                let (start, end) = (format!("{} start", self.name), format!("{} end", self.name));
+                let _ = performance.mark(&end);
+                let _ = performance.measure_with_start_mark_and_end_mark(self.name, &start, &end);
+            }
+        }
+    }
+}
+
+//Not there in workers, they only get console timings
+#[cfg(target_arch = "wasm32")]
+fn performance() -> Option<web_sys::Performance> {
+    web_sys::window().and_then(|window| window.performance())
+}
@@ -0,0 +1,124 @@
+//Messages between the page and a Web Worker running the simulation. The page posts Commands,
+//the worker answers each with one Response, both as plain objects tagged with their "type"
+
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use error::Error;
+use universe::{Cell, Universe};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Command {
+    //Start over with an empty width x height board, optionally with a B/S rule
+    Init { width: u32, height: u32, rule: Option<String> },
+    Tick { generations: u32 },
+    //Packed [row0, col0, row1, col1, ...] of the cells to set
+    SetCells { cells: Vec<u32>, alive: bool },
+    SnapshotRequest,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Response {
+    Ready { width: u32, height: u32 },
+    //Indices of the cells flipped by the command
+    Delta { generation: u32, population: u32, changed: Vec<u32> },
+    //Every cell, one byte each (1 alive)
+    Snapshot { width: u32, height: u32, generation: u32, population: u32, cells: Vec<u8> },
+    Error { message: String },
+}
+
+//Worker side state, the universe exists once Init came in
+#[wasm_bindgen]
+#[derive(Debug, Default)]
+pub struct Worker {
+    universe: Option<Universe>,
+}
+
+#[wasm_bindgen]
+impl Worker {
+    pub fn new() -> Worker {
+        Worker::default()
+    }
+
+    //Handle a Command object straight from a message event, the result is meant for postMessage
+    #[wasm_bindgen(js_name = handle)]
+    pub fn handle_js(&mut self, message: JsValue) -> Result<JsValue, JsValue> {
+        let command = serde_wasm_bindgen::from_value(message)?;
+        Ok(serde_wasm_bindgen::to_value(&self.handle(command))?)
+    }
+}
+
+impl Worker {
+    pub fn handle(&mut self, command: Command) -> Response {
+        self.run(command).unwrap_or_else(|message| Response::Error { message: message.to_string() })
+    }
+
+    fn run(&mut self, command: Command) -> Result<Response, Error> {
+        if let Command::Init { width, height, rule } = command {
+            Universe::check_size(width, height)?;
+            let mut universe = Universe::empty(width, height);
+            if let Some(rule) = rule {
+                universe.set_rule(rule.parse()?);
+            }
+            self.universe = Some(universe);
+            return Ok(Response::Ready { width, height });
+        }
+        let universe = self.universe.as_mut().ok_or_else(|| Error::Invalid("no universe yet, send Init first".to_string()))?;
+        let before = universe.get_cells();
+        match command {
+            Command::Tick { generations } => universe.tick_n(generations),
+            Command::SetCells { cells, alive } => {
+                let state = if alive { Cell::Alive } else { Cell::Dead };
+                for coords in cells.chunks(2).filter(|coords| coords.len() == 2) {
+                    universe.set_cell(coords[0], coords[1], state);
+                }
+            }
+            _ => return Ok(snapshot(universe)),
+        }
+        //Auto expansion may have resized the board, the old indices mean nothing then
+        if before.len() != (universe.width() * universe.height()) as usize {
+            return Ok(snapshot(universe));
+        }
+        let changed = universe.get_cells().iter().zip(&before).enumerate()
+            .filter(|(_, (now, before))| now != before)
+            .map(|(idx, _)| idx as u32)
+            .collect();
+        Ok(Response::Delta { generation: universe.generation(), population: universe.population(), changed })
+    }
+}
+
+fn snapshot(universe: &Universe) -> Response {
+    Response::Snapshot {
+        width: universe.width(),
+        height: universe.height(),
+        generation: universe.generation(),
+        population: universe.population(),
+        cells: universe.get_cells().iter().map(|&cell| cell as u8).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_answer_commands_with_deltas_and_snapshots() {
+        let mut worker = Worker::new();
+        assert!(matches!(worker.handle(Command::SnapshotRequest), Response::Error { .. }));
+        assert_eq!(worker.handle(Command::Init { width: 5, height: 5, rule: Some("B3/S23".to_string()) }),
+            Response::Ready { width: 5, height: 5 });
+        assert_eq!(worker.handle(Command::SetCells { cells: vec![2, 1, 2, 2, 2, 3], alive: true }),
+            Response::Delta { generation: 0, population: 3, changed: vec![11, 12, 13] });
+        assert_eq!(worker.handle(Command::Tick { generations: 1 }),
+            Response::Delta { generation: 1, population: 3, changed: vec![7, 11, 13, 17] });
+        assert_eq!(worker.handle(Command::Tick { generations: 2 }),
+            Response::Delta { generation: 3, population: 3, changed: vec![] });
+        match worker.handle(Command::SnapshotRequest) {
+            Response::Snapshot { width: 5, height: 5, generation: 3, cells, .. } => assert_eq!(cells[7..18].iter().sum::<u8>(), 3),
+            other => panic!("unexpected {:?}", other),
+        }
+        let bad_rule = Command::Init { width: 2, height: 2, rule: Some("X".to_string()) };
+        assert!(matches!(worker.handle(bad_rule), Response::Error { .. }));
+    }
+}
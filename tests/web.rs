@@ -2,6 +2,7 @@
 
 #![cfg(target_arch = "wasm32")]
 
+extern crate js_sys;
 extern crate wasm_game_of_life;
 use wasm_game_of_life::universe::Universe;
 
@@ -11,25 +12,22 @@ use wasm_bindgen_test::*;
 wasm_bindgen_test_configure!(run_in_browser);
 
 #[wasm_bindgen_test]
+#[allow(clippy::eq_op)]
 fn pass() {
     assert_eq!(1 + 1, 2);
 }
 
 #[cfg(test)]
 pub fn input_spaceship() -> Universe {
-    let mut universe = Universe::new();
-    universe.set_width(6);
-    universe.set_height(6);
-    universe.set_cells(&[(1,2), (2,3), (3,1), (3,2), (3,3)]);
+    let mut universe = Universe::empty(6, 6);
+    universe.set_cells(&[(1,2), (2,3), (3,1), (3,2), (3,3)]).unwrap();
     universe
 }
 
 #[cfg(test)]
 pub fn expected_spaceship() -> Universe {
-    let mut universe = Universe::new();
-    universe.set_width(6);
-    universe.set_height(6);
-    universe.set_cells(&[(2,1), (2,3), (3,2), (3,3), (4,2)]);
+    let mut universe = Universe::empty(6, 6);
+    universe.set_cells(&[(2,1), (2,3), (3,2), (3,3), (4,2)]).unwrap();
     universe
 }
 
@@ -45,4 +43,48 @@ pub fn test_tick() {
     // Call `tick` and then see if the cells in the `Universe`s are the same.
     input_universe.tick();
     assert_eq!(&input_universe.get_cells(), &expected_universe.get_cells());
-}
\ No newline at end of file
+}
+
+#[wasm_bindgen_test]
+pub fn test_cells_view() {
+    let universe = input_spaceship();
+    let view = universe.cells_view();
+    assert_eq!(view.length(), universe.cell_words() * 8);
+    // Cell (1, 2) is index 8, the lowest bit of the second byte.
+    assert_eq!(view.get_index(1) & 1, 1);
+}
+
+#[wasm_bindgen_test]
+pub fn test_on_tick() {
+    let mut universe = input_spaceship();
+    universe.set_on_tick(Some(js_sys::Function::new_with_args("generation, population",
+        "globalThis.lastTick = [generation, population];")));
+    universe.tick();
+    let last = js_sys::Reflect::get(&js_sys::global(), &"lastTick".into()).unwrap();
+    let last: Vec<f64> = js_sys::Array::from(&last).iter().filter_map(|value| value.as_f64()).collect();
+    assert_eq!(last, vec![1.0, 5.0]);
+}
+
+#[wasm_bindgen_test]
+pub fn test_on_cell_changed() {
+    let changed = || {
+        let last = js_sys::Reflect::get(&js_sys::global(), &"lastChanged".into()).unwrap();
+        js_sys::Array::from(&last).iter().filter_map(|value| value.as_f64()).collect::<Vec<f64>>()
+    };
+    let callback = || js_sys::Function::new_with_args("indices",
+        "globalThis.lastChanged = Array.from(indices).sort((a, b) => a - b);");
+
+    // Two cells die and two are born: (1, 2), (2, 1), (3, 1) and (4, 2).
+    let mut universe = input_spaceship();
+    universe.set_on_cell_changed(Some(callback()));
+    universe.tick();
+    assert_eq!(changed(), vec![8.0, 13.0, 19.0, 26.0]);
+
+    // Growing the board moves every cell, so all of them are reported.
+    let mut universe = input_spaceship();
+    universe.set_auto_expand(2);
+    universe.set_on_cell_changed(Some(callback()));
+    universe.tick();
+    assert_eq!(changed().len() as u32, universe.width() * universe.height());
+    assert!(universe.width() > 6);
+}